@@ -2,9 +2,9 @@ use anyhow::anyhow;
 use breez_sdk_spark::{
     BitcoinPaymentMethod, BreezSdk, InputType, LightningPaymentMethod, ListPaymentsRequest,
     LnurlPaymentMethod, PickedPaymentMethod, PrepareReceivePaymentRequest,
-    PrepareSendBitcoinRequest, PrepareSendLightningRequest, PrepareSendLnurlPayRequest,
-    ReceiveMethod, ReceivePaymentRequest, SendBitcoinRequest, SendLightningRequest,
-    SendLnurlPayRequest, parse,
+    PrepareSendBitcoinRequest, PrepareSendLightningRequest, PrepareSendLiquidAddressRequest,
+    PrepareSendLnurlPayRequest, ReceiveMethod, ReceivePaymentRequest, SendBitcoinRequest,
+    SendLightningRequest, SendLiquidAddressRequest, SendLnurlPayRequest, parse,
 };
 use clap::Parser;
 use rustyline::{
@@ -115,11 +115,18 @@ pub(crate) async fn execute_command(
                 _ => return Err(anyhow::anyhow!("Not a payment request")),
             };
 
-            let picked = sdk.pick_payment_method(payment_request).await?;
-            match picked {
+            let spendable_balance_msat = sdk.get_info().await?.spendable_balance_msat;
+            let picked = sdk
+                .pick_payment_method(payment_request, spendable_balance_msat, None)
+                .await?;
+            if let Some(description) = &picked.description {
+                println!("Description: {description}");
+            }
+            let amount_msat = picked.amount_msat.or(amount.map(|amount| amount * 1000));
+            match picked.payment_method {
                 PickedPaymentMethod::Bitcoin(bitcoin_payment_method) => {
-                    let amount =
-                        amount.ok_or(anyhow!("Amount is required for Bitcoin payments"))?;
+                    let amount_msat =
+                        amount_msat.ok_or(anyhow!("Amount is required for Bitcoin payments"))?;
                     match &bitcoin_payment_method {
                         BitcoinPaymentMethod::BitcoinAddress(bitcoin_address) => {
                             println!("Bitcoin address: {}", bitcoin_address.address)
@@ -136,7 +143,7 @@ pub(crate) async fn execute_command(
                     )?;
                     let prepared = sdk
                         .prepare_send_bitcoin(PrepareSendBitcoinRequest {
-                            amount_msat: amount * 1000,
+                            amount_msat,
                             method: bitcoin_payment_method,
                             fee_rate_sat_per_kw: Some(rate.parse()?),
                         })
@@ -191,6 +198,10 @@ pub(crate) async fn execute_command(
                             println!("Lightning address: {}", address)
                         }
                     }
+                    println!(
+                        "Description: {}",
+                        lnurl_payment_request.request.metadata.description
+                    );
 
                     let amount_msat = match (
                         lnurl_payment_request.request.min_sendable,
@@ -213,13 +224,31 @@ pub(crate) async fn execute_command(
                             lnurl_pay: lnurl_payment_request,
                             amount_msat,
                             comment,
+                            zap: None,
+                            payer_details: None,
                         })
                         .await?;
                     let result = sdk.send_lnurl_pay(SendLnurlPayRequest { prepared }).await?;
                     print_value(&result)?;
                     Ok(true)
                 }
-                PickedPaymentMethod::LiquidAddress(_liquid_address) => todo!(),
+                PickedPaymentMethod::LiquidAddress(liquid_address) => {
+                    let amount_msat =
+                        amount_msat.ok_or(anyhow!("Amount is required for Liquid payments"))?;
+                    println!("Liquid address: {}", liquid_address.address);
+                    let prepared = sdk
+                        .prepare_send_liquid_address(PrepareSendLiquidAddressRequest {
+                            address: liquid_address,
+                            amount_msat,
+                        })
+                        .await?;
+                    print_value(&prepared)?;
+                    let result = sdk
+                        .send_liquid_address(SendLiquidAddressRequest { prepared })
+                        .await?;
+                    print_value(&result)?;
+                    Ok(true)
+                }
             }
         }
         Command::ReceiveOnchain => {
@@ -23,7 +23,7 @@ struct Cli {
     #[arg(short, long, default_value = "./.data")]
     data_dir: String,
 
-    /// Network to use (mainnet, regtest)
+    /// Network to use (mainnet, testnet, signet, regtest)
     #[arg(long, default_value = "regtest")]
     network: String,
 }
@@ -102,6 +102,8 @@ async fn run_interactive_mode(data_dir: PathBuf, network: Network) -> Result<()>
         network: network.clone(),
         mnemonic: mnemonic.to_string(),
         data_dir: wallet_data_dir.to_string_lossy().to_string(),
+        payment_method_preference: None,
+        dns: None,
     };
     let sdk = breez_sdk_spark::connect(ConnectRequest { config }).await?;
 
@@ -111,13 +113,10 @@ async fn run_interactive_mode(data_dir: PathBuf, network: Network) -> Result<()>
     println!("Breez SDK CLI Interactive Mode");
     println!("Type 'help' for available commands or 'exit' to quit");
 
-    let cli_prompt = match network {
-        Network::Mainnet => "breez-spark-cli [mainnet]> ",
-        Network::Regtest => "breez-spark-cli [regtest]> ",
-    };
+    let cli_prompt = format!("breez-spark-cli [{network}]> ");
 
     loop {
-        let readline = rl.readline(cli_prompt);
+        let readline = rl.readline(&cli_prompt);
         match readline {
             Ok(line) => {
                 let trimmed = line.trim();
@@ -179,11 +178,10 @@ async fn main() -> Result<(), anyhow::Error> {
     })
     .await?;
 
-    let network = match cli.network.to_lowercase().as_str() {
-        "regtest" => Network::Regtest,
-        "mainnet" => Network::Mainnet,
-        _ => return Err(anyhow!("Invalid network. Use 'regtest' or 'mainnet'")),
-    };
+    let network: Network =
+        cli.network.to_lowercase().parse().map_err(|_| {
+            anyhow!("Invalid network. Use 'mainnet', 'testnet', 'signet' or 'regtest'")
+        })?;
 
     run_interactive_mode(data_dir, network).await?;
 
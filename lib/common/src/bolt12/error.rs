@@ -0,0 +1,10 @@
+use thiserror::Error;
+
+pub type Bolt12Result<T, E = Bolt12Error> = Result<T, E>;
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
+pub enum Bolt12Error {
+    #[error("invalid invoice request: {0}")]
+    InvalidInvoiceRequest(String),
+}
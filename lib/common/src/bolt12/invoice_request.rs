@@ -0,0 +1,187 @@
+use bech32::{NoChecksum, primitives::decode::CheckedHrpstring};
+use lightning::{
+    offers::{invoice_request::InvoiceRequest, offer::Offer, refund::Refund},
+    util::ser::Writeable,
+};
+
+use super::{
+    INVOICE_REQUEST_HRP,
+    error::{Bolt12Error, Bolt12Result},
+};
+
+/// TLV types reserved for offer fields, see <https://github.com/lightning/bolts/blob/master/12-offer-encoding.md>
+const OFFER_TLV_TYPES: std::ops::Range<u64> = 1..80;
+const EXPERIMENTAL_OFFER_TLV_TYPES: std::ops::Range<u64> = 1_000_000_000..2_000_000_000;
+
+/// A decoded `lnr` string.
+///
+/// Invoice requests that were not made for an offer are refunds: the payer asks the scanning
+/// wallet for an invoice it will then pay.
+pub enum DecodedInvoiceRequest {
+    InvoiceRequest(Box<InvoiceRequest>),
+    Refund(Box<Refund>),
+}
+
+pub fn decode_invoice_request(input: &str) -> Bolt12Result<DecodedInvoiceRequest> {
+    if let Ok(refund) = input.parse::<Refund>() {
+        return Ok(DecodedInvoiceRequest::Refund(Box::new(refund)));
+    }
+
+    let bytes = decode_bech32(input, INVOICE_REQUEST_HRP)?;
+    let invoice_request = InvoiceRequest::try_from(bytes)
+        .map_err(|e| Bolt12Error::InvalidInvoiceRequest(format!("{e:?}")))?;
+    Ok(DecodedInvoiceRequest::InvoiceRequest(Box::new(
+        invoice_request,
+    )))
+}
+
+/// Extracts the offer the invoice request was made for, by keeping only the offer TLV records.
+pub fn offer_from_invoice_request(invoice_request: &InvoiceRequest) -> Bolt12Result<Offer> {
+    let bytes = invoice_request.encode();
+    let mut offer_bytes = Vec::with_capacity(bytes.len());
+    let mut remaining = bytes.as_slice();
+    while !remaining.is_empty() {
+        let (tlv_type, record_len) = read_tlv_record(remaining).ok_or_else(|| {
+            Bolt12Error::InvalidInvoiceRequest("malformed tlv stream".to_string())
+        })?;
+        let (record, rest) = remaining.split_at(record_len);
+        if OFFER_TLV_TYPES.contains(&tlv_type) || EXPERIMENTAL_OFFER_TLV_TYPES.contains(&tlv_type) {
+            offer_bytes.extend_from_slice(record);
+        }
+        remaining = rest;
+    }
+
+    Offer::try_from(offer_bytes).map_err(|e| Bolt12Error::InvalidInvoiceRequest(format!("{e:?}")))
+}
+
+fn decode_bech32(input: &str, expected_hrp: &str) -> Bolt12Result<Vec<u8>> {
+    // Bolt12 strings may be split into chunks joined with '+', optionally followed by whitespace.
+    let encoded: String = input
+        .chars()
+        .filter(|c| *c != '+' && !c.is_whitespace())
+        .collect();
+    let parsed = CheckedHrpstring::new::<NoChecksum>(&encoded)
+        .map_err(|e| Bolt12Error::InvalidInvoiceRequest(e.to_string()))?;
+    if parsed.hrp().lowercase_char_iter().ne(expected_hrp.chars()) {
+        return Err(Bolt12Error::InvalidInvoiceRequest(format!(
+            "expected hrp {expected_hrp}"
+        )));
+    }
+
+    Ok(parsed.byte_iter().collect())
+}
+
+/// Reads the type of the TLV record at the start of `bytes`, returning it together with the
+/// total length of the record.
+fn read_tlv_record(bytes: &[u8]) -> Option<(u64, usize)> {
+    let (tlv_type, type_len) = read_bigsize(bytes)?;
+    let (value_len, len_len) = read_bigsize(bytes.get(type_len..)?)?;
+    let record_len = type_len
+        .checked_add(len_len)?
+        .checked_add(usize::try_from(value_len).ok()?)?;
+    if record_len > bytes.len() {
+        return None;
+    }
+
+    Some((tlv_type, record_len))
+}
+
+fn read_bigsize(bytes: &[u8]) -> Option<(u64, usize)> {
+    let (first, rest) = bytes.split_first()?;
+    let width = match first {
+        0xfd => 2,
+        0xfe => 4,
+        0xff => 8,
+        _ => return Some((u64::from(*first), 1)),
+    };
+    let mut buf = [0u8; 8];
+    buf[8_usize.checked_sub(width)?..].copy_from_slice(rest.get(..width)?);
+    Some((u64::from_be_bytes(buf), width.checked_add(1)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bech32::{Hrp, NoChecksum};
+    use bitcoin::secp256k1::{Keypair, Secp256k1, SecretKey};
+    use lightning::{
+        ln::{channelmanager::PaymentId, inbound_payment::ExpandedKey},
+        offers::{
+            nonce::Nonce,
+            offer::{OfferBuilder, Quantity},
+            refund::RefundBuilder,
+        },
+        util::ser::Writeable,
+    };
+
+    use super::{DecodedInvoiceRequest, decode_invoice_request, offer_from_invoice_request};
+    use crate::bolt12::INVOICE_REQUEST_HRP;
+
+    fn keypair(byte: u8) -> Keypair {
+        let secp = Secp256k1::new();
+        Keypair::from_secret_key(&secp, &SecretKey::from_slice(&[byte; 32]).unwrap())
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_decode_refund() {
+        let payer = keypair(1);
+        let refund = RefundBuilder::new(vec![1; 32], payer.public_key(), 10_000)
+            .unwrap()
+            .description("refund".to_string())
+            .absolute_expiry(Duration::from_secs(2_000_000_000))
+            .payer_note("thanks".to_string())
+            .build()
+            .unwrap();
+
+        let decoded = decode_invoice_request(&refund.to_string()).unwrap();
+        let DecodedInvoiceRequest::Refund(decoded) = decoded else {
+            panic!("expected refund");
+        };
+        assert_eq!(decoded.amount_msats(), 10_000);
+        assert_eq!(decoded.payer_signing_pubkey(), payer.public_key());
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_decode_invoice_request_for_offer() {
+        let secp = Secp256k1::new();
+        let issuer = keypair(2);
+        let offer = OfferBuilder::new(issuer.public_key())
+            .amount_msats(5_000)
+            .description("coffee".to_string())
+            .supported_quantity(Quantity::Unbounded)
+            .build()
+            .unwrap();
+        let expanded_key = ExpandedKey::new([3; 32]);
+        let nonce = Nonce::try_from(&[4; 16][..]).unwrap();
+        let invoice_request = offer
+            .request_invoice(&expanded_key, nonce, &secp, PaymentId([5; 32]))
+            .unwrap()
+            .quantity(2)
+            .unwrap()
+            .build_and_sign()
+            .unwrap();
+        let encoded = bech32::encode::<NoChecksum>(
+            Hrp::parse(INVOICE_REQUEST_HRP).unwrap(),
+            &invoice_request.encode(),
+        )
+        .unwrap();
+
+        let decoded = decode_invoice_request(&encoded).unwrap();
+        let DecodedInvoiceRequest::InvoiceRequest(decoded) = decoded else {
+            panic!("expected invoice request");
+        };
+        assert_eq!(decoded.amount_msats(), Some(10_000));
+        assert_eq!(decoded.quantity(), Some(2));
+        assert_eq!(offer_from_invoice_request(&decoded).unwrap(), offer);
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_decode_invalid_invoice_request() {
+        assert!(decode_invoice_request("lnr1invalid").is_err());
+        assert!(
+            decode_invoice_request("lno1zcss9mk8y3wkklfvevcrszlmu23kfrxh49px20665dqwmn4p72pksese")
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod error;
+pub mod invoice_request;
+
+const INVOICE_REQUEST_HRP: &str = "lnr";
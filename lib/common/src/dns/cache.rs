@@ -0,0 +1,85 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use web_time::{Duration, Instant};
+
+/// Caches lookups for the TTL of their records. When full, the entry expiring first is evicted.
+pub(super) struct TtlCache<V> {
+    capacity: usize,
+    entries: Mutex<HashMap<String, (V, Instant)>>,
+}
+
+impl<V: Clone> TtlCache<V> {
+    pub(super) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(super) fn get(&self, key: &str) -> Option<V> {
+        let mut entries = self.entries.lock().ok()?;
+        let (value, expires_at) = entries.get(key)?;
+        if *expires_at > Instant::now() {
+            return Some(value.clone());
+        }
+
+        entries.remove(key);
+        None
+    }
+
+    pub(super) fn insert(&self, key: String, value: V, ttl: Duration) {
+        if self.capacity == 0 || ttl.is_zero() {
+            return;
+        }
+        let now = Instant::now();
+        let (Ok(mut entries), Some(expires_at)) = (self.entries.lock(), now.checked_add(ttl))
+        else {
+            return;
+        };
+
+        entries.retain(|_, (_, expires_at)| *expires_at > now);
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            let first_expiring = entries
+                .iter()
+                .min_by_key(|(_, (_, expires_at))| *expires_at)
+                .map(|(key, _)| key.clone());
+            if let Some(first_expiring) = first_expiring {
+                entries.remove(&first_expiring);
+            }
+        }
+        entries.insert(key, (value, expires_at));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use web_time::Duration;
+
+    use super::TtlCache;
+
+    #[breez_sdk_macros::test_all]
+    fn test_ttl_cache() {
+        let cache = TtlCache::new(2);
+        cache.insert("a".to_string(), 1, Duration::from_secs(100));
+        cache.insert("b".to_string(), 2, Duration::from_secs(50));
+        assert_eq!(cache.get("a"), Some(1));
+        assert_eq!(cache.get("b"), Some(2));
+
+        // The entry expiring first is evicted
+        cache.insert("c".to_string(), 3, Duration::from_secs(150));
+        assert_eq!(cache.get("a"), Some(1));
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("c"), Some(3));
+
+        // Records without a TTL are not cached
+        cache.insert("d".to_string(), 4, Duration::ZERO);
+        assert_eq!(cache.get("d"), None);
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_ttl_cache_disabled() {
+        let cache = TtlCache::new(0);
+        cache.insert("a".to_string(), 1, Duration::from_secs(100));
+        assert_eq!(cache.get("a"), None);
+    }
+}
@@ -0,0 +1,88 @@
+use std::net::SocketAddr;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Configures how DNS lookups, like those of BIP-353 addresses, are resolved.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct DnsConfig {
+    /// The upstream resolvers, tried in order
+    pub upstreams: Vec<DnsUpstream>,
+    /// The timeout of a single query, in seconds
+    pub timeout_secs: u64,
+    /// The maximum number of cached lookups. Lookups are cached for the TTL of their records.
+    pub cache_size: u32,
+}
+
+impl Default for DnsConfig {
+    fn default() -> Self {
+        Self {
+            upstreams: vec![DnsUpstream::Https {
+                address: "1.1.1.1:443".to_string(),
+                server_name: "cloudflare-dns.com".to_string(),
+            }],
+            timeout_secs: 5,
+            cache_size: 32,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum DnsUpstream {
+    /// DNS-over-HTTPS, queried at `https://<server_name>:<port>/dns-query` with the port of the
+    /// socket `address`. On WASM the server is reached by its name, elsewhere at the IP of its
+    /// `address`.
+    Https {
+        address: String,
+        server_name: String,
+    },
+    /// Plain DNS at the socket `address`. DNSSEC proofs are fetched over TCP. Not supported on
+    /// WASM.
+    Udp { address: String },
+}
+
+impl DnsUpstream {
+    /// The DNS-over-HTTPS endpoint of the upstream, if it is one.
+    pub(super) fn doh_url(&self) -> Result<Option<String>> {
+        match self {
+            DnsUpstream::Https {
+                address,
+                server_name,
+            } => {
+                let port = address.parse::<SocketAddr>()?.port();
+                Ok(Some(format!("https://{server_name}:{port}/dns-query")))
+            }
+            DnsUpstream::Udp { .. } => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DnsUpstream;
+
+    #[breez_sdk_macros::test_all]
+    fn test_doh_url() {
+        let upstream = DnsUpstream::Https {
+            address: "1.1.1.1:8443".to_string(),
+            server_name: "cloudflare-dns.com".to_string(),
+        };
+        assert_eq!(
+            upstream.doh_url().unwrap().as_deref(),
+            Some("https://cloudflare-dns.com:8443/dns-query")
+        );
+
+        let upstream = DnsUpstream::Https {
+            address: "cloudflare-dns.com".to_string(),
+            server_name: "cloudflare-dns.com".to_string(),
+        };
+        assert!(upstream.doh_url().is_err());
+
+        let upstream = DnsUpstream::Udp {
+            address: "1.1.1.1:53".to_string(),
+        };
+        assert_eq!(upstream.doh_url().unwrap(), None);
+    }
+}
@@ -0,0 +1,197 @@
+use anyhow::{Result, anyhow, ensure};
+use dnssec_prover::{
+    query::{ProofBuilder, QueryBuf},
+    rr::{Name, RR, TXT_TYPE},
+    ser::parse_rr_stream,
+    validation::verify_rr_stream,
+};
+use reqwest::Client;
+use tracing::debug;
+use web_time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::{DnsConfig, DnsUpstream, VerifiedTxtRecords, cache::TtlCache};
+
+/// Builds RFC 9102 DNSSEC proofs by querying the configured upstreams. DNS-over-HTTPS upstreams
+/// are supported on all targets, plain DNS upstreams over TCP elsewhere than on WASM.
+pub(super) struct DnssecProver {
+    client: Client,
+    upstreams: Vec<DnsUpstream>,
+    timeout: Duration,
+    cache: TtlCache<VerifiedTxtRecords>,
+}
+
+impl DnssecProver {
+    pub(super) fn new(config: &DnsConfig) -> Result<Self> {
+        let builder = Client::builder();
+        // Browsers resolve the server names themselves, elsewhere the DNS-over-HTTPS servers are
+        // reached at their address
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        let builder =
+            config
+                .upstreams
+                .iter()
+                .try_fold(builder, |builder, upstream| match upstream {
+                    DnsUpstream::Https {
+                        address,
+                        server_name,
+                    } => Ok::<_, anyhow::Error>(builder.resolve(server_name, address.parse()?)),
+                    DnsUpstream::Udp { .. } => Ok(builder),
+                })?;
+        Ok(Self {
+            client: builder.build()?,
+            upstreams: config.upstreams.clone(),
+            timeout: Duration::from_secs(config.timeout_secs),
+            cache: TtlCache::new(config.cache_size as usize),
+        })
+    }
+
+    /// Looks up the TXT records of the name by building their DNSSEC proof, trying the upstreams
+    /// in order. The records are only returned when the proof verifies.
+    pub(super) async fn verified_txt_lookup(&self, dns_name: &str) -> Result<VerifiedTxtRecords> {
+        let name = to_name(dns_name)?;
+        if let Some(verified) = self.cache.get(&name) {
+            return Ok(verified);
+        }
+
+        let mut last_err = anyhow!("No upstream can build DNSSEC proofs");
+        for upstream in &self.upstreams {
+            let result = match upstream {
+                DnsUpstream::Https { .. } => self.build_doh_proof(upstream, &name).await,
+                #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+                DnsUpstream::Udp { address } => self.build_tcp_proof(address, &name).await,
+                // Plain DNS upstreams are rejected when building the WASM resolver
+                #[cfg(all(target_family = "wasm", target_os = "unknown"))]
+                DnsUpstream::Udp { .. } => continue,
+            };
+            let verified = result.and_then(|(proof, ttl)| {
+                let records = verify_txt_proof(dns_name, &proof)?;
+                Ok((VerifiedTxtRecords { records, proof }, ttl))
+            });
+            let (verified, ttl) = match verified {
+                Ok(verified) => verified,
+                Err(e) => {
+                    debug!("Failed to get a valid DNSSEC proof from {upstream:?}: {e}");
+                    last_err = e;
+                    continue;
+                }
+            };
+
+            self.cache.insert(
+                name.to_string(),
+                verified.clone(),
+                Duration::from_secs(ttl.into()),
+            );
+            return Ok(verified);
+        }
+        Err(last_err)
+    }
+
+    async fn build_doh_proof(&self, upstream: &DnsUpstream, name: &Name) -> Result<(Vec<u8>, u32)> {
+        let url = upstream
+            .doh_url()?
+            .ok_or_else(|| anyhow!("Not a DNS-over-HTTPS upstream"))?;
+        let (mut builder, query) = ProofBuilder::new(name, TXT_TYPE);
+        let mut queries = vec![query];
+        while builder.awaiting_responses() {
+            let query = queries
+                .pop()
+                .ok_or_else(|| anyhow!("DNSSEC proof is missing queries"))?;
+            let response = self.send_doh_query(&url, &query).await?;
+            let next_queries = builder
+                .process_response(&response)
+                .map_err(|e| anyhow!("Failed to build DNSSEC proof: {e}"))?;
+            queries.extend(next_queries);
+        }
+        builder
+            .finish_proof()
+            .map_err(|()| anyhow!("Failed to build DNSSEC proof"))
+    }
+
+    async fn send_doh_query(&self, url: &str, query: &[u8]) -> Result<QueryBuf> {
+        let response = self
+            .client
+            .post(url)
+            .body(query.to_vec())
+            .header("Accept", "application/dns-message")
+            .header("Content-Type", "application/dns-message")
+            .timeout(self.timeout)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        let mut buf = QueryBuf::new_zeroed(0);
+        buf.extend_from_slice(&response);
+        Ok(buf)
+    }
+
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    async fn build_tcp_proof(&self, address: &str, name: &Name) -> Result<(Vec<u8>, u32)> {
+        let address = address.parse()?;
+        let proof = tokio::time::timeout(
+            self.timeout,
+            dnssec_prover::query::build_txt_proof_async(address, name),
+        )
+        .await??;
+        Ok(proof)
+    }
+}
+
+/// Verifies the RFC 9102 DNSSEC proof from the root trust anchors, returning the TXT records of
+/// the name it proves.
+pub fn verify_txt_proof(dns_name: &str, proof: &[u8]) -> Result<Vec<String>> {
+    let name = to_name(dns_name)?;
+    let rrs = parse_rr_stream(proof).map_err(|()| anyhow!("Invalid DNSSEC proof"))?;
+    let verified =
+        verify_rr_stream(&rrs).map_err(|e| anyhow!("DNSSEC proof verification failed: {e:?}"))?;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    ensure!(
+        verified.valid_from <= now && now <= verified.expires,
+        "DNSSEC proof is only valid from {} until {}",
+        verified.valid_from,
+        verified.expires
+    );
+
+    Ok(verified
+        .resolve_name(&name)
+        .into_iter()
+        .filter_map(|rr| match rr {
+            RR::Txt(txt) => String::from_utf8(txt.data.as_vec()).ok(),
+            _ => None,
+        })
+        .collect())
+}
+
+fn to_name(dns_name: &str) -> Result<Name> {
+    // DNSSEC names are fully qualified, ending with the root label
+    let dns_name = if dns_name.ends_with('.') {
+        dns_name.to_string()
+    } else {
+        format!("{dns_name}.")
+    };
+    Name::try_from(dns_name).map_err(|()| anyhow!("Invalid DNS name"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_name, verify_txt_proof};
+
+    #[breez_sdk_macros::test_all]
+    fn test_to_name() {
+        assert_eq!(
+            to_name("user.user._bitcoin-payment.example.com")
+                .unwrap()
+                .as_str(),
+            "user.user._bitcoin-payment.example.com."
+        );
+        assert_eq!(to_name("example.com.").unwrap().as_str(), "example.com.");
+        assert!(to_name("invalid name.com").is_err());
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_verify_invalid_proof() {
+        assert!(verify_txt_proof("user._bitcoin-payment.example.com", &[]).is_err());
+        assert!(verify_txt_proof("user._bitcoin-payment.example.com", &[0; 32]).is_err());
+    }
+}
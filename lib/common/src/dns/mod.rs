@@ -1,3 +1,6 @@
+mod cache;
+mod config;
+mod dnssec;
 #[cfg_attr(
     all(target_family = "wasm", target_os = "unknown"),
     path = "resolver_wasm.rs"
@@ -5,9 +8,23 @@
 mod resolver;
 
 use anyhow::Result;
+pub use config::{DnsConfig, DnsUpstream};
+pub use dnssec::verify_txt_proof;
 pub use resolver::Resolver;
 
+/// TXT records proven with DNSSEC.
+#[derive(Clone, Debug)]
+pub struct VerifiedTxtRecords {
+    pub records: Vec<String>,
+    /// The serialized RFC 9102 DNSSEC proof of the records, see [`verify_txt_proof`]
+    pub proof: Vec<u8>,
+}
+
 #[breez_sdk_macros::async_trait]
 pub trait DnsResolver {
     async fn txt_lookup(&self, dns_name: String) -> Result<Vec<String>>;
+
+    /// Looks up the TXT records of the name along with their DNSSEC proof. Fails when the
+    /// records cannot be proven from the root trust anchors.
+    async fn verified_txt_lookup(&self, dns_name: String) -> Result<VerifiedTxtRecords>;
 }
@@ -1,27 +1,60 @@
+use std::net::SocketAddr;
+
 use anyhow::Result;
 use hickory_resolver::TokioResolver;
-use hickory_resolver::config::{ResolverConfig, ResolverOpts};
+use hickory_resolver::config::{NameServerConfig, ResolverConfig, ResolverOpts};
 use hickory_resolver::name_server::TokioConnectionProvider;
+use hickory_resolver::proto::xfer::Protocol;
+
+use crate::utils::Arc;
 
-use super::DnsResolver;
+use super::{DnsConfig, DnsResolver, DnsUpstream, VerifiedTxtRecords, dnssec::DnssecProver};
 
+/// Resolves names with the configured upstreams. Its cache is shared by all clones, so it can
+/// also resolve the hosts of a [`crate::rest::ReqwestRestClient`].
+#[derive(Clone)]
 pub struct Resolver {
     resolver: TokioResolver,
+    prover: Arc<DnssecProver>,
 }
 
 impl Resolver {
     pub fn new() -> Self {
+        Self::with_config(&DnsConfig::default()).expect("default DNS config is valid")
+    }
+
+    pub fn with_config(config: &DnsConfig) -> Result<Self> {
+        let mut resolver_config = ResolverConfig::new();
+        for upstream in &config.upstreams {
+            let name_server = match upstream {
+                DnsUpstream::Https {
+                    address,
+                    server_name,
+                } => NameServerConfig {
+                    tls_dns_name: Some(server_name.clone()),
+                    ..NameServerConfig::new(address.parse::<SocketAddr>()?, Protocol::Https)
+                },
+                DnsUpstream::Udp { address } => {
+                    NameServerConfig::new(address.parse::<SocketAddr>()?, Protocol::Udp)
+                }
+            };
+            resolver_config.add_name_server(name_server);
+        }
+
         let mut opts = ResolverOpts::default();
         opts.validate = true;
+        opts.timeout = std::time::Duration::from_secs(config.timeout_secs);
+        opts.cache_size = config.cache_size as usize;
 
-        let resolver = TokioResolver::builder_with_config(
-            ResolverConfig::default(),
-            TokioConnectionProvider::default(),
-        )
-        .with_options(opts)
-        .build();
+        let resolver =
+            TokioResolver::builder_with_config(resolver_config, TokioConnectionProvider::default())
+                .with_options(opts)
+                .build();
 
-        Self { resolver }
+        Ok(Self {
+            resolver,
+            prover: Arc::new(DnssecProver::new(config)?),
+        })
     }
 }
 
@@ -41,4 +74,20 @@ impl DnsResolver for Resolver {
             .collect();
         Ok(records)
     }
+
+    async fn verified_txt_lookup(&self, dns_name: String) -> Result<VerifiedTxtRecords> {
+        self.prover.verified_txt_lookup(&dns_name).await
+    }
+}
+
+impl reqwest::dns::Resolve for Resolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let resolver = self.resolver.clone();
+        Box::pin(async move {
+            let lookup = resolver.lookup_ip(name.as_str()).await?;
+            let addrs: Vec<SocketAddr> = lookup.iter().map(|ip| SocketAddr::new(ip, 0)).collect();
+            let addrs: reqwest::dns::Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
 }
@@ -1,32 +1,64 @@
-use anyhow::{Result, anyhow};
+use anyhow::{Result, anyhow, ensure};
 use dns_parser::{Builder, Packet, RData, ResponseCode};
 use dns_parser::{QueryClass, QueryType};
 use reqwest::Client;
+use tracing::debug;
+use web_time::Duration;
 
-use super::DnsResolver;
+use crate::utils::Arc;
 
-pub struct Resolver;
+use super::{DnsConfig, DnsResolver, VerifiedTxtRecords, cache::TtlCache, dnssec::DnssecProver};
+
+/// Resolves names with the DNS-over-HTTPS upstreams of the config. Its cache is shared by all
+/// clones.
+#[derive(Clone)]
+pub struct Resolver {
+    client: Client,
+    doh_urls: Vec<String>,
+    timeout: Duration,
+    cache: Arc<TtlCache<Vec<String>>>,
+    prover: Arc<DnssecProver>,
+}
 
 impl Resolver {
     pub fn new() -> Self {
-        Self
+        Self::with_config(&DnsConfig::default()).expect("default DNS config is valid")
     }
-}
 
-#[breez_sdk_macros::async_trait]
-impl DnsResolver for Resolver {
-    async fn txt_lookup(&self, dns_name: String) -> Result<Vec<String>> {
+    pub fn with_config(config: &DnsConfig) -> Result<Self> {
+        let doh_urls = config
+            .upstreams
+            .iter()
+            .map(|upstream| {
+                upstream
+                    .doh_url()?
+                    .ok_or_else(|| anyhow!("Only DNS-over-HTTPS upstreams are supported on WASM"))
+            })
+            .collect::<Result<Vec<String>>>()?;
+        ensure!(!doh_urls.is_empty(), "No DNS upstream is configured");
+
+        Ok(Self {
+            client: Client::builder().build()?,
+            doh_urls,
+            timeout: Duration::from_secs(config.timeout_secs),
+            cache: Arc::new(TtlCache::new(config.cache_size as usize)),
+            prover: Arc::new(DnssecProver::new(config)?),
+        })
+    }
+
+    async fn doh_txt_lookup(&self, url: &str, dns_name: &str) -> Result<(Vec<String>, u32)> {
         let mut builder = Builder::new_query(1, true);
-        builder.add_question(&dns_name, false, QueryType::TXT, QueryClass::IN);
+        builder.add_question(dns_name, false, QueryType::TXT, QueryClass::IN);
         let req_bytes = builder
             .build()
             .map_err(|_| anyhow!("Error building DNS query"))?;
-        let client = Client::builder().build()?;
-        let res_bytes = client
-            .post("https://cloudflare-dns.com/dns-query")
+        let res_bytes = self
+            .client
+            .post(url)
             .body(req_bytes)
             .header("Accept", "application/dns-message")
             .header("Content-Type", "application/dns-message")
+            .timeout(self.timeout)
             .send()
             .await?
             .error_for_status()?
@@ -39,6 +71,12 @@ impl DnsResolver for Resolver {
                 packet.header.response_code
             ));
         }
+        let ttl = packet
+            .answers
+            .iter()
+            .map(|answer| answer.ttl)
+            .min()
+            .unwrap_or_default();
         let res = packet
             .answers
             .into_iter()
@@ -56,6 +94,41 @@ impl DnsResolver for Resolver {
             })
             .collect::<Vec<_>>();
 
-        Ok(res)
+        Ok((res, ttl))
+    }
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[breez_sdk_macros::async_trait]
+impl DnsResolver for Resolver {
+    async fn txt_lookup(&self, dns_name: String) -> Result<Vec<String>> {
+        if let Some(records) = self.cache.get(&dns_name) {
+            return Ok(records);
+        }
+
+        let mut last_err = anyhow!("No DNS-over-HTTPS upstream configured");
+        for url in &self.doh_urls {
+            match self.doh_txt_lookup(url, &dns_name).await {
+                Ok((records, ttl)) => {
+                    self.cache
+                        .insert(dns_name, records.clone(), Duration::from_secs(ttl.into()));
+                    return Ok(records);
+                }
+                Err(e) => {
+                    debug!("Failed to look up {dns_name} with {url}: {e}");
+                    last_err = e;
+                }
+            }
+        }
+        Err(last_err)
+    }
+
+    async fn verified_txt_lookup(&self, dns_name: String) -> Result<VerifiedTxtRecords> {
+        self.prover.verified_txt_lookup(&dns_name).await
     }
 }
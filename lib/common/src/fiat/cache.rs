@@ -0,0 +1,307 @@
+use std::sync::Mutex;
+
+use anyhow::Result;
+use maybe_sync::{MaybeSend, MaybeSync};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+use web_time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{error::ServiceConnectivityError, utils::Arc};
+
+use super::{FiatAPI, Rate};
+
+/// How long fetched rates are served before being refreshed
+pub const FIAT_RATES_REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Exchange rates along with when they were fetched from the server
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct FiatRates {
+    pub rates: Vec<Rate>,
+    /// The unix timestamp, in seconds, of when the rates were fetched
+    pub fetched_at: u64,
+}
+
+/// Where the rates returned by [`FiatRateCache::rates`] come from
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FiatRatesStatus {
+    /// The rates were fetched within the refresh interval
+    Fresh,
+    /// The rates were just fetched from the server
+    Refreshed,
+    /// The server is unreachable and the rates are the last fetched ones
+    Stale,
+}
+
+/// Persists the last fetched rates, so they remain available when offline
+#[breez_sdk_macros::async_trait]
+pub trait FiatRateStore: MaybeSend + MaybeSync {
+    async fn load_fiat_rates(&self) -> Result<Option<FiatRates>>;
+    async fn save_fiat_rates(&self, rates: &FiatRates) -> Result<()>;
+}
+
+/// Caches the rates of a [`FiatAPI`], falling back to the persisted rates when the server is
+/// unreachable.
+pub struct FiatRateCache {
+    fiat_api: Arc<dyn FiatAPI>,
+    store: Arc<dyn FiatRateStore>,
+    refresh_interval: Duration,
+    rates: Mutex<Option<FiatRates>>,
+}
+
+impl FiatRateCache {
+    pub fn new(
+        fiat_api: Arc<dyn FiatAPI>,
+        store: Arc<dyn FiatRateStore>,
+        refresh_interval: Duration,
+    ) -> Self {
+        Self {
+            fiat_api,
+            store,
+            refresh_interval,
+            rates: Mutex::new(None),
+        }
+    }
+
+    /// Returns the cached rates when fetched within the refresh interval, refreshing them
+    /// otherwise. When the server is unreachable, the last fetched rates are returned as
+    /// [`FiatRatesStatus::Stale`].
+    pub async fn rates(&self) -> Result<(FiatRates, FiatRatesStatus), ServiceConnectivityError> {
+        let cached = self.cached_rates().await;
+        if let Some(cached) = &cached {
+            let age = now().saturating_sub(cached.fetched_at);
+            if age < self.refresh_interval.as_secs() {
+                return Ok((cached.clone(), FiatRatesStatus::Fresh));
+            }
+        }
+
+        match self.refresh().await {
+            Ok(rates) => Ok((rates, FiatRatesStatus::Refreshed)),
+            Err(e) => match cached {
+                Some(cached) => {
+                    warn!("Failed to refresh fiat rates, serving cached rates: {e}");
+                    Ok((cached, FiatRatesStatus::Stale))
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    /// Fetches the rates from the server, caching and persisting them.
+    pub async fn refresh(&self) -> Result<FiatRates, ServiceConnectivityError> {
+        let rates = FiatRates {
+            rates: self.fiat_api.fetch_fiat_rates().await?,
+            fetched_at: now(),
+        };
+        if let Err(e) = self.store.save_fiat_rates(&rates).await {
+            warn!("Failed to persist fiat rates: {e}");
+        }
+        if let Ok(mut cached) = self.rates.lock() {
+            *cached = Some(rates.clone());
+        }
+        Ok(rates)
+    }
+
+    /// Refreshes the rates every refresh interval until `shutdown` is signalled, calling
+    /// `on_update` with the refreshed rates.
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    pub async fn refresh_periodically<F, Fut>(
+        &self,
+        on_update: F,
+        mut shutdown: tokio::sync::watch::Receiver<()>,
+    ) where
+        F: Fn(FiatRates) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        loop {
+            tokio::select! {
+                _ = shutdown.changed() => {
+                    debug!("Stopping the fiat rates refresh");
+                    return;
+                }
+                () = tokio::time::sleep(self.refresh_interval) => {
+                    match self.refresh().await {
+                        Ok(rates) => on_update(rates).await,
+                        Err(e) => warn!("Failed to refresh fiat rates: {e}"),
+                    }
+                }
+            }
+        }
+    }
+
+    /// The rates in memory, loaded from the store on first use
+    async fn cached_rates(&self) -> Option<FiatRates> {
+        if let Some(rates) = self.rates.lock().ok()?.clone() {
+            return Some(rates);
+        }
+
+        let stored = match self.store.load_fiat_rates().await {
+            Ok(stored) => stored?,
+            Err(e) => {
+                warn!("Failed to load persisted fiat rates: {e}");
+                return None;
+            }
+        };
+        let mut cached = self.rates.lock().ok()?;
+        // Rates refreshed meanwhile are more recent than the stored ones
+        Some(cached.get_or_insert(stored).clone())
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        sync::{
+            Mutex,
+            atomic::{AtomicU32, Ordering},
+        },
+    };
+
+    use anyhow::Result;
+    use web_time::Duration;
+
+    use crate::{
+        error::{ServiceConnectivityError, ServiceConnectivityErrorKind},
+        fiat::{FiatAPI, FiatCurrency, Rate},
+        utils::Arc,
+    };
+
+    use super::{
+        FIAT_RATES_REFRESH_INTERVAL, FiatRateCache, FiatRateStore, FiatRates, FiatRatesStatus, now,
+    };
+
+    #[derive(Default)]
+    struct MockFiatAPI {
+        responses: Mutex<VecDeque<Option<f64>>>,
+        calls: AtomicU32,
+    }
+
+    impl MockFiatAPI {
+        /// Queues the USD rate of the next fetch, or a connectivity error when `None`
+        fn add_response(&self, usd_rate: Option<f64>) -> &Self {
+            self.responses.lock().unwrap().push_back(usd_rate);
+            self
+        }
+    }
+
+    #[breez_sdk_macros::async_trait]
+    impl FiatAPI for MockFiatAPI {
+        async fn fetch_fiat_currencies(
+            &self,
+        ) -> Result<Vec<FiatCurrency>, ServiceConnectivityError> {
+            Ok(Vec::new())
+        }
+
+        async fn fetch_fiat_rates(&self) -> Result<Vec<Rate>, ServiceConnectivityError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            match self.responses.lock().unwrap().pop_front().flatten() {
+                Some(value) => Ok(vec![Rate {
+                    coin: "USD".to_string(),
+                    value,
+                }]),
+                None => Err(ServiceConnectivityError::new(
+                    ServiceConnectivityErrorKind::Connect,
+                    "offline".to_string(),
+                )),
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct MockFiatRateStore {
+        rates: Mutex<Option<FiatRates>>,
+    }
+
+    #[breez_sdk_macros::async_trait]
+    impl FiatRateStore for MockFiatRateStore {
+        async fn load_fiat_rates(&self) -> Result<Option<FiatRates>> {
+            Ok(self.rates.lock().unwrap().clone())
+        }
+
+        async fn save_fiat_rates(&self, rates: &FiatRates) -> Result<()> {
+            *self.rates.lock().unwrap() = Some(rates.clone());
+            Ok(())
+        }
+    }
+
+    #[allow(clippy::float_cmp)]
+    fn assert_usd_rate(rates: &FiatRates, expected: f64) {
+        assert_eq!(rates.rates[0].value, expected);
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_rates_are_cached() {
+        let fiat_api = Arc::new(MockFiatAPI::default());
+        fiat_api.add_response(Some(60_000.0));
+        let store = Arc::new(MockFiatRateStore::default());
+        let cache =
+            FiatRateCache::new(fiat_api.clone(), store.clone(), FIAT_RATES_REFRESH_INTERVAL);
+
+        let (rates, status) = cache.rates().await.unwrap();
+        assert_eq!(status, FiatRatesStatus::Refreshed);
+        assert_usd_rate(&rates, 60_000.0);
+        assert!(rates.fetched_at <= now());
+        assert!(store.rates.lock().unwrap().is_some());
+
+        let (rates, status) = cache.rates().await.unwrap();
+        assert_eq!(status, FiatRatesStatus::Fresh);
+        assert_usd_rate(&rates, 60_000.0);
+        assert_eq!(fiat_api.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_stale_rates_are_served_offline() {
+        let fiat_api = Arc::new(MockFiatAPI::default());
+        fiat_api
+            .add_response(None)
+            .add_response(Some(61_000.0))
+            .add_response(None);
+        let store = Arc::new(MockFiatRateStore::default());
+        let stored = FiatRates {
+            rates: vec![Rate {
+                coin: "USD".to_string(),
+                value: 60_000.0,
+            }],
+            fetched_at: 1_700_000_000,
+        };
+        *store.rates.lock().unwrap() = Some(stored);
+        let cache =
+            FiatRateCache::new(fiat_api.clone(), store.clone(), FIAT_RATES_REFRESH_INTERVAL);
+
+        // The persisted rates are served when the server is unreachable
+        let (rates, status) = cache.rates().await.unwrap();
+        assert_eq!(status, FiatRatesStatus::Stale);
+        assert_usd_rate(&rates, 60_000.0);
+        assert_eq!(rates.fetched_at, 1_700_000_000);
+
+        let (rates, status) = cache.rates().await.unwrap();
+        assert_eq!(status, FiatRatesStatus::Refreshed);
+        assert_usd_rate(&rates, 61_000.0);
+        assert_usd_rate(&store.rates.lock().unwrap().clone().unwrap(), 61_000.0);
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_rates_unavailable() {
+        let fiat_api = Arc::new(MockFiatAPI::default());
+        fiat_api.add_response(None).add_response(Some(61_000.0));
+        let store = Arc::new(MockFiatRateStore::default());
+        let cache = FiatRateCache::new(fiat_api.clone(), store, Duration::ZERO);
+
+        assert!(cache.rates().await.is_err());
+
+        // Rates are refreshed on every call without a refresh interval
+        let (_, status) = cache.rates().await.unwrap();
+        assert_eq!(status, FiatRatesStatus::Refreshed);
+        let (rates, status) = cache.rates().await.unwrap();
+        assert_eq!(status, FiatRatesStatus::Stale);
+        assert_usd_rate(&rates, 61_000.0);
+    }
+}
@@ -0,0 +1,278 @@
+use thiserror::Error;
+
+use super::{CurrencyInfo, FiatCurrency, LocaleOverrides, Rate, Symbol};
+
+const MSAT_PER_BTC: f64 = 100_000_000_000.0;
+/// Isolates right-to-left symbols, so they render on the right side of the amount
+const RIGHT_TO_LEFT_ISOLATE: char = '\u{2067}';
+const POP_DIRECTIONAL_ISOLATE: char = '\u{2069}';
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
+pub enum FiatError {
+    #[error("Invalid fiat amount: {0}")]
+    InvalidAmount(f64),
+    #[error("Invalid exchange rate: {0}")]
+    InvalidRate(f64),
+    #[error("Rate of {rate_coin} used to convert {currency_id}")]
+    RateMismatch {
+        currency_id: String,
+        rate_coin: String,
+    },
+    #[error("Unsupported fraction size: {0}")]
+    UnsupportedFractionSize(u32),
+}
+
+/// Converts an amount in millisatoshi to `currency`, rounded to its fraction size.
+pub fn msat_to_fiat(
+    amount_msat: u64,
+    currency: &FiatCurrency,
+    rate: &Rate,
+) -> Result<f64, FiatError> {
+    validate_rate(currency, rate)?;
+    let amount = msat_to_f64(amount_msat) / MSAT_PER_BTC * rate.value;
+    round_to_fraction(amount, currency.info.fraction_size)
+}
+
+/// Converts an amount of `currency`, rounded to its fraction size, to millisatoshi.
+pub fn fiat_to_msat(amount: f64, currency: &FiatCurrency, rate: &Rate) -> Result<u64, FiatError> {
+    validate_rate(currency, rate)?;
+    if !amount.is_finite() || amount < 0.0 {
+        return Err(FiatError::InvalidAmount(amount));
+    }
+    let amount = round_to_fraction(amount, currency.info.fraction_size)?;
+    f64_to_msat((amount / rate.value * MSAT_PER_BTC).round())
+        .ok_or(FiatError::InvalidAmount(amount))
+}
+
+/// Formats an amount of `currency`, rounded to its fraction size, for a locale such as `en-US`.
+///
+/// The symbol is placed following its template, or else its position and the currency spacing,
+/// as overridden for the locale. Currencies without a symbol are formatted with their code.
+pub fn format_fiat(
+    amount: f64,
+    currency: &FiatCurrency,
+    locale: Option<&str>,
+) -> Result<String, FiatError> {
+    if !amount.is_finite() {
+        return Err(FiatError::InvalidAmount(amount));
+    }
+    let info = &currency.info;
+    let precision = usize::try_from(info.fraction_size)
+        .map_err(|_| FiatError::UnsupportedFractionSize(info.fraction_size))?;
+    let amount = round_to_fraction(amount, info.fraction_size)?;
+    let number = format!("{:.precision$}", amount.abs());
+    let sign = if amount < 0.0 { "-" } else { "" };
+
+    let overrides = locale.and_then(|locale| locale_overrides(info, locale));
+    let symbol = overridden_symbol(info.symbol.as_ref(), overrides.map(|o| &o.symbol));
+    let mut spacing = overrides
+        .and_then(|o| o.spacing)
+        .or(info.spacing)
+        .unwrap_or(0);
+    let grapheme = symbol.grapheme.unwrap_or_else(|| {
+        // A currency code is unreadable when stuck to the amount
+        spacing = spacing.max(1);
+        currency.id.clone()
+    });
+    let grapheme = if symbol.rtl.unwrap_or(false) {
+        format!("{RIGHT_TO_LEFT_ISOLATE}{grapheme}{POP_DIRECTIONAL_ISOLATE}")
+    } else {
+        grapheme
+    };
+
+    // The template places the amount at `1` and the symbol at `$`
+    let formatted = if let Some(template) = symbol.template {
+        template
+            .chars()
+            .map(|c| match c {
+                '1' => number.clone(),
+                '$' => grapheme.clone(),
+                c => c.to_string(),
+            })
+            .collect()
+    } else {
+        let space = " ".repeat(usize::try_from(spacing).unwrap_or_default());
+        if symbol.position == Some(1) {
+            format!("{number}{space}{grapheme}")
+        } else {
+            format!("{grapheme}{space}{number}")
+        }
+    };
+    Ok(format!("{sign}{formatted}"))
+}
+
+/// The overrides of the locale, or else of its language
+fn locale_overrides<'a>(info: &'a CurrencyInfo, locale: &str) -> Option<&'a LocaleOverrides> {
+    let locale = locale.replace('_', "-");
+    let language = locale.split('-').next().unwrap_or_default();
+    info.locale_overrides
+        .iter()
+        .find(|o| o.locale.eq_ignore_ascii_case(&locale))
+        .or_else(|| {
+            info.locale_overrides
+                .iter()
+                .find(|o| o.locale.eq_ignore_ascii_case(language))
+        })
+}
+
+fn overridden_symbol(symbol: Option<&Symbol>, overrides: Option<&Symbol>) -> Symbol {
+    let symbol = symbol.cloned().unwrap_or(Symbol {
+        grapheme: None,
+        template: None,
+        rtl: None,
+        position: None,
+    });
+    let Some(overrides) = overrides else {
+        return symbol;
+    };
+    Symbol {
+        grapheme: overrides.grapheme.clone().or(symbol.grapheme),
+        // An overridden position replaces the template
+        template: if overrides.position.is_some() {
+            overrides.template.clone()
+        } else {
+            overrides.template.clone().or(symbol.template)
+        },
+        rtl: overrides.rtl.or(symbol.rtl),
+        position: overrides.position.or(symbol.position),
+    }
+}
+
+fn validate_rate(currency: &FiatCurrency, rate: &Rate) -> Result<(), FiatError> {
+    if !rate.coin.eq_ignore_ascii_case(&currency.id) {
+        return Err(FiatError::RateMismatch {
+            currency_id: currency.id.clone(),
+            rate_coin: rate.coin.clone(),
+        });
+    }
+    if !rate.value.is_finite() || rate.value <= 0.0 {
+        return Err(FiatError::InvalidRate(rate.value));
+    }
+    Ok(())
+}
+
+fn round_to_fraction(amount: f64, fraction_size: u32) -> Result<f64, FiatError> {
+    let factor = 10_u32
+        .checked_pow(fraction_size)
+        .map(f64::from)
+        .ok_or(FiatError::UnsupportedFractionSize(fraction_size))?;
+    Ok((amount * factor).round() / factor)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn msat_to_f64(amount_msat: u64) -> f64 {
+    amount_msat as f64
+}
+
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+fn f64_to_msat(amount_msat: f64) -> Option<u64> {
+    (amount_msat < u64::MAX as f64).then_some(amount_msat as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::fiat::{CurrencyInfo, FiatCurrency, Rate};
+
+    use super::{FiatError, fiat_to_msat, format_fiat, msat_to_fiat};
+
+    fn currency(id: &str) -> FiatCurrency {
+        let mut currencies: HashMap<String, CurrencyInfo> =
+            serde_json::from_str(include_str!("../../assets/json/currencies.json")).unwrap();
+        FiatCurrency {
+            id: id.to_string(),
+            info: currencies.remove(id).unwrap(),
+        }
+    }
+
+    fn rate(coin: &str, value: f64) -> Rate {
+        Rate {
+            coin: coin.to_string(),
+            value,
+        }
+    }
+
+    #[allow(clippy::float_cmp)]
+    #[breez_sdk_macros::test_all]
+    fn test_msat_to_fiat() {
+        let usd = currency("USD");
+        let usd_rate = rate("USD", 60_000.0);
+        assert_eq!(
+            msat_to_fiat(100_000_000_000, &usd, &usd_rate).unwrap(),
+            60_000.0
+        );
+        // 0.6 cents rounds up to a cent
+        assert_eq!(msat_to_fiat(10_000, &usd, &usd_rate).unwrap(), 0.01);
+        assert_eq!(msat_to_fiat(1_000, &usd, &usd_rate).unwrap(), 0.0);
+
+        let jpy = currency("JPY");
+        assert_eq!(
+            msat_to_fiat(12_345_678_000, &jpy, &rate("JPY", 9_000_000.0)).unwrap(),
+            1_111_111.0
+        );
+
+        assert!(matches!(
+            msat_to_fiat(1_000, &usd, &rate("EUR", 55_000.0)),
+            Err(FiatError::RateMismatch { .. })
+        ));
+        assert!(matches!(
+            msat_to_fiat(1_000, &usd, &rate("USD", 0.0)),
+            Err(FiatError::InvalidRate(_))
+        ));
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_fiat_to_msat() {
+        let usd = currency("USD");
+        let usd_rate = rate("USD", 50_000.0);
+        assert_eq!(
+            fiat_to_msat(50_000.0, &usd, &usd_rate).unwrap(),
+            100_000_000_000
+        );
+        assert_eq!(fiat_to_msat(1.0, &usd, &usd_rate).unwrap(), 2_000_000);
+        // Fractions of a cent are dropped before converting
+        assert_eq!(fiat_to_msat(1.004, &usd, &usd_rate).unwrap(), 2_000_000);
+
+        for amount in [-1.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                fiat_to_msat(amount, &usd, &usd_rate),
+                Err(FiatError::InvalidAmount(_))
+            ));
+        }
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_format_fiat() {
+        let usd = currency("USD");
+        assert_eq!(format_fiat(1234.567, &usd, None).unwrap(), "$1234.57");
+        assert_eq!(format_fiat(-5.0, &usd, Some("en-US")).unwrap(), "-$5.00");
+
+        // The position and spacing of the euro are overridden for some locales
+        let eur = currency("EUR");
+        assert_eq!(format_fiat(12.5, &eur, None).unwrap(), "12.50 €");
+        assert_eq!(format_fiat(12.5, &eur, Some("fr-FR")).unwrap(), "12.50 €");
+        assert_eq!(format_fiat(12.5, &eur, Some("en_GB")).unwrap(), "€12.50");
+        assert_eq!(format_fiat(12.5, &eur, Some("de-AT")).unwrap(), "€ 12.50");
+        assert_eq!(format_fiat(12.5, &eur, Some("de-DE")).unwrap(), "12.50 €");
+
+        let jpy = currency("JPY");
+        assert_eq!(format_fiat(1_111.4, &jpy, None).unwrap(), "¥1111");
+
+        let aed = currency("AED");
+        assert_eq!(
+            format_fiat(10.0, &aed, None).unwrap(),
+            "10.00 \u{2067}د.إ\u{2069}"
+        );
+
+        let aoa = currency("AOA");
+        assert_eq!(format_fiat(10.0, &aoa, None).unwrap(), "AOA 10.00");
+
+        assert!(format_fiat(f64::NAN, &usd, None).is_err());
+    }
+}
@@ -1,3 +1,6 @@
+mod cache;
+mod convert;
+
 use std::collections::HashMap;
 
 use maybe_sync::{MaybeSend, MaybeSync};
@@ -10,6 +13,11 @@ use crate::{
     with_connection_retry,
 };
 
+pub use cache::{
+    FIAT_RATES_REFRESH_INTERVAL, FiatRateCache, FiatRateStore, FiatRates, FiatRatesStatus,
+};
+pub use convert::{FiatError, fiat_to_msat, format_fiat, msat_to_fiat};
+
 /// Details about a supported currency in the fiat rate feed
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -49,7 +57,7 @@ impl FiatAPI for BreezServer {
             .map(|r| r.coin.clone())
             .collect::<Vec<String>>();
 
-        let data = include_str!("../assets/json/currencies.json");
+        let data = include_str!("../../assets/json/currencies.json");
         let fiat_currency_map: HashMap<String, CurrencyInfo> =
             serde_json::from_str(data).map_err(|e| {
                 ServiceConnectivityError::new(
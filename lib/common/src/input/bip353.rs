@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+
+use crate::dns::DnsResolver;
+
+use super::{
+    PaymentRequestSource,
+    error::Bip353Error,
+    parser::{has_bip_21_prefix, parse_bip_21},
+};
+
+const BIP_353_USER_BITCOIN_PAYMENT_PREFIX: &str = "user._bitcoin-payment";
+
+/// The DNS TXT record publishing the payment instructions of a BIP-353 address, see
+/// <https://github.com/bitcoin/bips/blob/master/bip-0353.mediawiki>
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct Bip353Record {
+    /// The `user@domain` address, without the ₿ prefix
+    pub address: String,
+    /// The name of the TXT record, like `user.user._bitcoin-payment.domain`
+    pub name: String,
+    /// The `bitcoin:` URI the TXT record must contain. Values longer than 255 bytes have to be
+    /// split into multiple strings of the same record.
+    pub value: String,
+}
+
+/// Builds the TXT record publishing a BOLT12 offer at the BIP-353 address, with an optional
+/// on-chain address and silent payment address as fallbacks.
+pub fn build_bip353_record(
+    address: &str,
+    offer: &str,
+    bitcoin_address: Option<&str>,
+    silent_payment_address: Option<&str>,
+) -> Result<Bip353Record, Bip353Error> {
+    let (local_part, domain) = split_bip353_address(address)
+        .ok_or_else(|| Bip353Error::InvalidAddress(address.to_string()))?;
+    if local_part.is_empty() || domain.is_empty() || local_part.len() > 63 || domain.len() > 63 {
+        return Err(Bip353Error::InvalidAddress(address.to_string()));
+    }
+
+    let silent_payment_param = silent_payment_address
+        .map(|address| format!("&sp={address}"))
+        .unwrap_or_default();
+    let value = format!(
+        "bitcoin:{}?lno={offer}{silent_payment_param}",
+        bitcoin_address.unwrap_or_default()
+    );
+    // Make sure payers can parse the payment instructions
+    parse_bip_21(&value, &PaymentRequestSource::default())?;
+
+    Ok(Bip353Record {
+        address: format!("{local_part}@{domain}"),
+        name: bip353_dns_name(local_part, domain),
+        value,
+    })
+}
+
+/// Checks the record is currently published with a valid DNSSEC proof, returning the proof.
+pub async fn verify_bip353_record<D: DnsResolver>(
+    dns_resolver: &D,
+    record: &Bip353Record,
+) -> Result<Vec<u8>, Bip353Error> {
+    let verified = dns_resolver
+        .verified_txt_lookup(record.name.clone())
+        .await
+        .map_err(|e| Bip353Error::DnsLookup(e.to_string()))?;
+    let published: Vec<String> = verified
+        .records
+        .into_iter()
+        .filter(|record| has_bip_21_prefix(record))
+        .collect();
+
+    match published.as_slice() {
+        [] => Err(Bip353Error::NotPublished),
+        [published] if published == &record.value => Ok(verified.proof),
+        [published] => Err(Bip353Error::Mismatch {
+            published: published.clone(),
+        }),
+        _ => Err(Bip353Error::MultipleRecords(
+            u32::try_from(published.len()).unwrap_or(u32::MAX),
+        )),
+    }
+}
+
+/// Splits a `₿user@domain` address into its local part and domain.
+pub(super) fn split_bip353_address(address: &str) -> Option<(&str, &str)> {
+    // BIP-353 addresses may have a ₿ prefix, so strip it if present
+    address.strip_prefix('₿').unwrap_or(address).split_once('@')
+}
+
+pub(super) fn bip353_dns_name(local_part: &str, domain: &str) -> String {
+    format!("{local_part}.{BIP_353_USER_BITCOIN_PAYMENT_PREFIX}.{domain}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::{Bip353Error, Bip353Record};
+    use crate::test_utils::mock_dns_resolver::MockDnsResolver;
+
+    use super::{build_bip353_record, verify_bip353_record};
+
+    const OFFER: &str = "lno1zcss9mk8y3wkklfvevcrszlmu23kfrxh49px20665dqwmn4p72pksese";
+    const BITCOIN_ADDRESS: &str = "bc1qxhmdufsvnuaaaer4ynz88fspdsxq2h9e9cetdj";
+    const SILENT_PAYMENT_ADDRESS: &str = "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc9pkqwv";
+
+    #[breez_sdk_macros::test_all]
+    fn test_build_bip353_record() {
+        let record = build_bip353_record("₿alice@example.com", OFFER, None, None).unwrap();
+        assert_eq!(
+            record,
+            Bip353Record {
+                address: "alice@example.com".to_string(),
+                name: "alice.user._bitcoin-payment.example.com".to_string(),
+                value: format!("bitcoin:?lno={OFFER}"),
+            }
+        );
+
+        let record = build_bip353_record(
+            "alice@example.com",
+            OFFER,
+            Some(BITCOIN_ADDRESS),
+            Some(SILENT_PAYMENT_ADDRESS),
+        )
+        .unwrap();
+        assert_eq!(
+            record.value,
+            format!("bitcoin:{BITCOIN_ADDRESS}?lno={OFFER}&sp={SILENT_PAYMENT_ADDRESS}")
+        );
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_build_invalid_bip353_record() {
+        assert!(matches!(
+            build_bip353_record("example.com", OFFER, None, None),
+            Err(Bip353Error::InvalidAddress(_))
+        ));
+        assert!(matches!(
+            build_bip353_record("@example.com", OFFER, None, None),
+            Err(Bip353Error::InvalidAddress(_))
+        ));
+        assert!(matches!(
+            build_bip353_record("alice@example.com", "lno1invalid", None, None),
+            Err(Bip353Error::InvalidPaymentInstructions(_))
+        ));
+        assert!(matches!(
+            build_bip353_record("alice@example.com", OFFER, Some("bc1qinvalid"), None),
+            Err(Bip353Error::InvalidPaymentInstructions(_))
+        ));
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_verify_bip353_record() {
+        let record = build_bip353_record("alice@example.com", OFFER, None, None).unwrap();
+        let dns_resolver = MockDnsResolver::new();
+
+        dns_resolver.add_response(vec![record.value.clone(), "v=spf1 -all".to_string()]);
+        assert!(verify_bip353_record(&dns_resolver, &record).await.is_ok());
+
+        dns_resolver.add_response(vec!["v=spf1 -all".to_string()]);
+        assert!(matches!(
+            verify_bip353_record(&dns_resolver, &record).await,
+            Err(Bip353Error::NotPublished)
+        ));
+
+        dns_resolver.add_response(vec![format!("bitcoin:{BITCOIN_ADDRESS}")]);
+        assert!(matches!(
+            verify_bip353_record(&dns_resolver, &record).await,
+            Err(Bip353Error::Mismatch { published }) if published == format!("bitcoin:{BITCOIN_ADDRESS}")
+        ));
+
+        dns_resolver.add_response(vec![record.value.clone(), record.value.clone()]);
+        assert!(matches!(
+            verify_bip353_record(&dns_resolver, &record).await,
+            Err(Bip353Error::MultipleRecords(2))
+        ));
+
+        assert!(matches!(
+            verify_bip353_record(&dns_resolver, &record).await,
+            Err(Bip353Error::DnsLookup(_))
+        ));
+    }
+}
@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-use crate::{error::ServiceConnectivityError, lnurl::error::LnurlError};
+use crate::{error::ServiceConnectivityError, lnurl::error::LnurlError, network::BitcoinNetwork};
 
 #[derive(Debug, Error)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
@@ -9,6 +9,15 @@ pub enum Bip21Error {
     InvalidAddress,
     #[error("bip21 contains invalid amount")]
     InvalidAmount,
+    #[error(
+        "bip21 amount of {amount_msat} msat does not match the invoice amount of {invoice_amount_msat} msat"
+    )]
+    AmountMismatch {
+        amount_msat: u64,
+        invoice_amount_msat: u64,
+    },
+    #[error("bip21 contains an amount without an asset id")]
+    MissingAssetId,
     #[error("bip21 contains invalid parameter value for '{0}'")]
     InvalidParameter(String),
     #[error("bip21 parameter missing equals character")]
@@ -33,6 +42,29 @@ impl Bip21Error {
     }
 }
 
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
+pub enum Bip353Error {
+    #[error("invalid BIP-353 address: {0}")]
+    InvalidAddress(String),
+    #[error("invalid payment instructions: {0}")]
+    InvalidPaymentInstructions(Bip21Error),
+    #[error("DNS lookup failed: {0}")]
+    DnsLookup(String),
+    #[error("no bitcoin: TXT record is published")]
+    NotPublished,
+    #[error("{0} bitcoin: TXT records are published instead of one")]
+    MultipleRecords(u32),
+    #[error("another bitcoin: TXT record is published: {published}")]
+    Mismatch { published: String },
+}
+
+impl From<Bip21Error> for Bip353Error {
+    fn from(value: Bip21Error) -> Self {
+        Self::InvalidPaymentInstructions(value)
+    }
+}
+
 #[derive(Debug, Error)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
 pub enum ParseError {
@@ -42,10 +74,20 @@ pub enum ParseError {
     Bip21Error(Bip21Error),
     #[error("invalid input")]
     InvalidInput,
+    #[error("invalid checksum")]
+    InvalidChecksum,
+    #[error("invalid network: {0}")]
+    InvalidNetwork(BitcoinNetwork),
+    #[error("invoice expired at {expired_at}")]
+    InvoiceExpired { expired_at: u64 },
+    #[error("Lightning address resolution error: {0}")]
+    LightningAddressResolution(LnurlError),
     #[error("Lnurl error: {0}")]
     LnurlError(LnurlError),
     #[error("Service connectivity error: {0}")]
     ServiceConnectivity(ServiceConnectivityError),
+    #[error("unsupported human-readable part: {0}")]
+    UnsupportedHrp(String),
 }
 
 impl From<Bip21Error> for ParseError {
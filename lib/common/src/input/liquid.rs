@@ -0,0 +1,268 @@
+use bech32::{Checksum, primitives::decode::CheckedHrpstring};
+use bitcoin::{base58, secp256k1::PublicKey};
+
+use crate::network::BitcoinNetwork;
+
+const BLINDING_PUBKEY_LEN: usize = 33;
+const PUBKEY_HASH_LEN: usize = 20;
+
+/// Address encoding parameters of a Liquid network, see
+/// <https://github.com/ElementsProject/elements/blob/master/src/chainparams.cpp>
+struct LiquidParams {
+    network: BitcoinNetwork,
+    p2pkh_prefix: u8,
+    p2sh_prefix: u8,
+    blinded_prefix: u8,
+    bech_hrp: &'static str,
+    blech_hrp: &'static str,
+    /// The hex encoded asset id of L-BTC
+    lbtc_asset_id: &'static str,
+}
+
+const LIQUID_PARAMS: [LiquidParams; 3] = [
+    LiquidParams {
+        network: BitcoinNetwork::Bitcoin,
+        p2pkh_prefix: 57,
+        p2sh_prefix: 39,
+        blinded_prefix: 12,
+        bech_hrp: "ex",
+        blech_hrp: "lq",
+        lbtc_asset_id: "6f0279e9ed041c3d710a9f57d0c02928416460c4b722ae3457a11eec381c526d",
+    },
+    LiquidParams {
+        network: BitcoinNetwork::Testnet3,
+        p2pkh_prefix: 36,
+        p2sh_prefix: 19,
+        blinded_prefix: 23,
+        bech_hrp: "tex",
+        blech_hrp: "tlq",
+        lbtc_asset_id: "144c654344aa716d6f3abcc1ca90e5641e4e2a7f633bc09fe3baf64585819a49",
+    },
+    LiquidParams {
+        network: BitcoinNetwork::Regtest,
+        p2pkh_prefix: 235,
+        p2sh_prefix: 75,
+        blinded_prefix: 4,
+        bech_hrp: "ert",
+        blech_hrp: "el",
+        lbtc_asset_id: "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225",
+    },
+];
+
+/// The checksum used for confidential segwit v0 addresses.
+enum Blech32 {}
+
+impl Checksum for Blech32 {
+    type MidstateRepr = u64;
+    const CODE_LENGTH: usize = 1024;
+    const CHECKSUM_LENGTH: usize = 12;
+    const GENERATOR_SH: [u64; 5] = [
+        0x7d_52fb_a40b_d886,
+        0x5e_8dbf_1a03_950c,
+        0x1c_3a3c_7407_2a18,
+        0x38_5d72_fa0e_5139,
+        0x70_93e5_a608_865b,
+    ];
+    const TARGET_RESIDUE: u64 = 1;
+}
+
+/// The checksum used for confidential segwit v1+ addresses.
+enum Blech32m {}
+
+impl Checksum for Blech32m {
+    type MidstateRepr = u64;
+    const CODE_LENGTH: usize = 1024;
+    const CHECKSUM_LENGTH: usize = 12;
+    const GENERATOR_SH: [u64; 5] = Blech32::GENERATOR_SH;
+    const TARGET_RESIDUE: u64 = 0x455_972a_3350_f7a1;
+}
+
+/// Decodes a confidential or unconfidential Liquid address, in either its base58 or its
+/// bech32/blech32 encoding, returning the network it is valid for.
+pub(super) fn decode_liquid_address(input: &str) -> Option<BitcoinNetwork> {
+    decode_base58_address(input).or_else(|| decode_segwit_address(input))
+}
+
+/// Returns whether the hex encoded asset id is the one of L-BTC on the network.
+pub(super) fn is_lbtc_asset_id(asset_id: &str, network: BitcoinNetwork) -> bool {
+    LIQUID_PARAMS.iter().any(|params| {
+        params.network == network && params.lbtc_asset_id.eq_ignore_ascii_case(asset_id)
+    })
+}
+
+/// Returns whether the human-readable part is used by Liquid segwit addresses.
+pub(super) fn is_liquid_hrp(hrp: &str) -> bool {
+    LIQUID_PARAMS
+        .iter()
+        .any(|params| params.bech_hrp == hrp || params.blech_hrp == hrp)
+}
+
+fn decode_base58_address(input: &str) -> Option<BitcoinNetwork> {
+    let data = base58::decode_check(input).ok()?;
+    let (prefix, payload) = data.split_first()?;
+    LIQUID_PARAMS.iter().find_map(|params| {
+        if *prefix == params.blinded_prefix {
+            // The blinded prefix is followed by the unconfidential address prefix and the
+            // blinding public key.
+            let (version, payload) = payload.split_first()?;
+            if *version != params.p2pkh_prefix && *version != params.p2sh_prefix {
+                return None;
+            }
+            if payload.len() != BLINDING_PUBKEY_LEN.checked_add(PUBKEY_HASH_LEN)? {
+                return None;
+            }
+            PublicKey::from_slice(&payload[..BLINDING_PUBKEY_LEN]).ok()?;
+            return Some(params.network);
+        }
+
+        if (*prefix == params.p2pkh_prefix || *prefix == params.p2sh_prefix)
+            && payload.len() == PUBKEY_HASH_LEN
+        {
+            return Some(params.network);
+        }
+
+        None
+    })
+}
+
+fn decode_segwit_address(input: &str) -> Option<BitcoinNetwork> {
+    if let Ok((hrp, _, _)) = bech32::segwit::decode(input) {
+        let hrp = hrp.to_lowercase();
+        return LIQUID_PARAMS
+            .iter()
+            .find(|params| params.bech_hrp == hrp)
+            .map(|params| params.network);
+    }
+
+    // Segwit v0 uses blech32, later versions blech32m.
+    let (checked, is_v0) = match CheckedHrpstring::new::<Blech32>(input) {
+        Ok(checked) => (checked, true),
+        Err(_) => (CheckedHrpstring::new::<Blech32m>(input).ok()?, false),
+    };
+    let params = LIQUID_PARAMS
+        .iter()
+        .find(|params| checked.hrp().to_lowercase() == params.blech_hrp)?;
+    let mut checked = checked;
+    let version = checked.remove_witness_version()?;
+    if (version.to_u8() == 0) != is_v0 {
+        return None;
+    }
+
+    let data: Vec<u8> = checked.byte_iter().collect();
+    if data.len() <= BLINDING_PUBKEY_LEN {
+        return None;
+    }
+    let (blinding_pubkey, program) = data.split_at(BLINDING_PUBKEY_LEN);
+    let valid_program_len = match version.to_u8() {
+        0 => program.len() == 20 || program.len() == 32,
+        _ => (2..=40).contains(&program.len()),
+    };
+    if !valid_program_len {
+        return None;
+    }
+    PublicKey::from_slice(blinding_pubkey).ok()?;
+
+    Some(params.network)
+}
+
+#[cfg(test)]
+mod tests {
+    use bech32::{
+        Checksum, Fe32, Hrp,
+        primitives::iter::{ByteIterExt, Fe32IterExt},
+    };
+
+    use super::{Blech32, Blech32m, decode_liquid_address, is_lbtc_asset_id};
+    use crate::network::BitcoinNetwork;
+
+    #[breez_sdk_macros::test_all]
+    fn test_blech32_sanity() {
+        Blech32::sanity_check();
+        Blech32m::sanity_check();
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_decode_base58_address() {
+        for (address, network) in [
+            (
+                "VJLCbLBTCdxhWyjVLdjcSmGAksVMtabYg15maSi93zknQD2ihC38R7CUd8KbDFnV8A4hiykxnRB3Uv6d",
+                BitcoinNetwork::Bitcoin,
+            ),
+            (
+                "Q7qcjTLsYGoMA7TjUp97R6E6AM5VKqBik6",
+                BitcoinNetwork::Bitcoin,
+            ),
+            (
+                "GqiQRsPEyJLAsEBFB5R34KHuqxDNkG3zur",
+                BitcoinNetwork::Bitcoin,
+            ),
+            (
+                "2dxmEBXc2qMYcLSKiDBxdEePY3Ytixmnh4E",
+                BitcoinNetwork::Regtest,
+            ),
+        ] {
+            assert_eq!(decode_liquid_address(address), Some(network), "{address}");
+        }
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_decode_segwit_address() {
+        let network = decode_liquid_address(
+            "el1qqw3e3mk4ng3ks43mh54udznuekaadh9lgwef3mwgzrfzakmdwcvqqve2xzutyaf7vjcap67f28q90uxec2ve95g3rpu5crapcmfr2l9xl5jzazvcpysz",
+        );
+        assert_eq!(network, Some(BitcoinNetwork::Regtest));
+
+        let mut blinded_program =
+            hex::decode("02c2511bd5da036893ef3f9b84d70de241adb02f481c7c65980aef4376190b634d")
+                .unwrap();
+        blinded_program.extend([7; 20]);
+        let confidential: String = blinded_program
+            .iter()
+            .copied()
+            .bytes_to_fes()
+            .with_checksum::<Blech32>(&Hrp::parse("tlq").unwrap())
+            .with_witness_version(Fe32::Q)
+            .chars()
+            .collect();
+        assert_eq!(
+            decode_liquid_address(&confidential),
+            Some(BitcoinNetwork::Testnet3)
+        );
+
+        let unconfidential =
+            bech32::segwit::encode_v0(Hrp::parse("ex").unwrap(), &[7; 20]).unwrap();
+        assert_eq!(
+            decode_liquid_address(&unconfidential),
+            Some(BitcoinNetwork::Bitcoin)
+        );
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_is_lbtc_asset_id() {
+        let mainnet_lbtc = "6f0279e9ed041c3d710a9f57d0c02928416460c4b722ae3457a11eec381c526d";
+        assert!(is_lbtc_asset_id(mainnet_lbtc, BitcoinNetwork::Bitcoin));
+        assert!(is_lbtc_asset_id(
+            &mainnet_lbtc.to_uppercase(),
+            BitcoinNetwork::Bitcoin
+        ));
+        assert!(!is_lbtc_asset_id(mainnet_lbtc, BitcoinNetwork::Testnet3));
+        // Tether USDt
+        assert!(!is_lbtc_asset_id(
+            "ce091c998b83c78bb71a632313ba3760f1763d9cfcffae02258ffa9865a37bd2",
+            BitcoinNetwork::Bitcoin
+        ));
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_decode_invalid_address() {
+        for address in [
+            "",
+            "1andreas3batLhQa2FawWjeyjCqyBzypd",
+            "bc1qxhmdufsvnuaaaer4ynz88fspdsxq2h9e9cetdj",
+            "VJLCbLBTCdxhWyjVLdjcSmGAksVMtabYg15maSi93zknQD2ihC38R7CUd8KbDFnV8A4hiykxnRB3Uv6e",
+            "el1qqw3e3mk4ng3ks43mh54udznuekaadh9lgwef3mwgzrfzakmdwcvqqve2xzutyaf7vjcap67f28q90uxec2ve95g3rpu5crapcmfr2l9xl5jzazvcpysy",
+        ] {
+            assert!(decode_liquid_address(address).is_none(), "{address}");
+        }
+    }
+}
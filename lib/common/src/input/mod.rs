@@ -1,7 +1,10 @@
+mod bip353;
 mod error;
+mod liquid;
 mod models;
 mod parser;
 
-pub use error::ParseError;
+pub use bip353::{Bip353Record, build_bip353_record, verify_bip353_record};
+pub use error::{Bip353Error, ParseError};
 pub use models::*;
-pub use parser::parse;
+pub use parser::{InputParser, parse, parse_for_network, parse_with_trace};
@@ -1,6 +1,12 @@
+use bitcoin::{Address, address::NetworkUnchecked};
 use serde::{Deserialize, Serialize};
 
-use crate::{lnurl::auth::LnurlAuthRequestData, network::BitcoinNetwork, utils::default_true};
+use super::liquid::is_lbtc_asset_id;
+use crate::{
+    lnurl::{auth::LnurlAuthRequestData, channel::LnurlChannelRequestData},
+    network::BitcoinNetwork,
+    utils::default_true,
+};
 
 /// Wrapper for the decrypted [`AesSuccessActionData`] payload
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -39,12 +45,20 @@ pub enum Amount {
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct Bip21 {
+    /// The amount to pay in sats. Not set for Liquid URIs of other assets than L-BTC, see
+    /// `asset_amount`.
     pub amount_sat: Option<u64>,
+    /// The amount of a Liquid asset other than L-BTC to pay, as given in the URI. Its precision
+    /// depends on the asset.
+    pub asset_amount: Option<String>,
+    /// The hex encoded Liquid asset id to pay.
     pub asset_id: Option<String>,
     pub uri: String,
     pub extras: Vec<Bip21Extra>,
     pub label: Option<String>,
     pub message: Option<String>,
+    /// The payjoin endpoint of the receiver, from the `pj` and `pjos` parameters
+    pub payjoin: Option<Payjoin>,
     pub payment_methods: Vec<PaymentMethod>,
 }
 
@@ -60,9 +74,21 @@ pub struct Bip21Extra {
 pub struct BitcoinAddress {
     pub address: String,
     pub network: BitcoinNetwork,
+    /// The payjoin endpoint the receiver accepts payjoin proposals for this address on
+    pub payjoin: Option<Payjoin>,
     pub source: PaymentRequestSource,
 }
 
+impl BitcoinAddress {
+    /// Returns whether the address can be paid on the given network. Test networks and regtest
+    /// share the legacy address encoding, so the address is checked against the network itself.
+    pub fn is_valid_for_network(&self, network: BitcoinNetwork) -> bool {
+        self.address
+            .parse::<Address<NetworkUnchecked>>()
+            .is_ok_and(|address| address.is_valid_for_network(network.into()))
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct Bolt11Invoice {
@@ -105,7 +131,24 @@ pub struct Bolt12Invoice {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct Bolt12InvoiceRequest {
-    // TODO: Fill fields
+    /// The absolute expiry time, in seconds since the Unix epoch, of an invoice request without
+    /// an offer (a refund).
+    pub absolute_expiry: Option<u64>,
+    /// The amount requested, in millisats. For invoice requests on an offer without an explicit
+    /// amount, this is derived from the offer amount and the quantity.
+    pub amount_msat: Option<u64>,
+    /// The chain hash of the chain the invoice should be paid on.
+    pub chain: String,
+    /// The description of an invoice request without an offer (a refund).
+    pub description: Option<String>,
+    pub invoice_request: String,
+    /// The offer this invoice request was made for, if any.
+    pub offer: Option<DetailedBolt12Offer>,
+    pub payer_note: Option<String>,
+    /// The public key of the payer, used to sign the invoice request.
+    pub payer_signing_pubkey: String,
+    pub quantity: Option<u64>,
+    pub source: PaymentRequestSource,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -163,6 +206,7 @@ pub struct DetailedBolt12Offer {
 #[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
 pub enum InputType {
     LnurlAuth(LnurlAuthRequestData),
+    LnurlChannel(LnurlChannelRequestData),
     PaymentRequest(PaymentScheme),
     ReceiveRequest(ReceiveRequest),
     Url(String),
@@ -179,10 +223,22 @@ pub struct LightningAddress {
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct LiquidAddress {
     pub address: String,
+    /// The hex encoded asset id requested by the URI the address was found in. Any asset can be
+    /// sent to the address when not set.
+    pub asset_id: Option<String>,
     pub network: BitcoinNetwork,
     pub source: PaymentRequestSource,
 }
 
+impl LiquidAddress {
+    /// Returns whether the address can be paid in L-BTC.
+    pub fn is_lbtc(&self) -> bool {
+        self.asset_id
+            .as_ref()
+            .is_none_or(|asset_id| is_lbtc_asset_id(asset_id, self.network))
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
@@ -193,9 +249,13 @@ pub struct LnurlPayRequest {
     /// The maximum amount, in millisats, that this LNURL-pay endpoint accepts
     pub max_sendable: u64,
     /// As per LUD-06, `metadata` is a raw string (e.g. a json representation of the inner map).
-    /// Use `metadata_vec()` to get the parsed items.
-    #[serde(rename(deserialize = "metadata"))]
+    /// The invoice description hash commits to it as is. See `metadata` for the parsed items.
+    #[serde(rename = "metadata")]
     pub metadata_str: String,
+    /// The parsed `metadata_str`, to be shown to the user when asking for payment input. It is
+    /// not serialized, restore it with [`crate::lnurl::pay::parse_metadata`] after deserializing.
+    #[serde(skip)]
+    pub metadata: LnurlPayMetadata,
     /// The comment length accepted by this endpoint
     ///
     /// See <https://github.com/lnurl/luds/blob/luds/12.md>
@@ -224,6 +284,69 @@ pub struct LnurlPayRequest {
     /// See <https://github.com/nostr-protocol/nips/blob/master/57.md>
     /// See <https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki>
     pub nostr_pubkey: Option<String>,
+
+    /// The payer identity the recipient asks for, if any.
+    ///
+    /// See <https://github.com/lnurl/luds/blob/luds/18.md>
+    #[serde(default)]
+    pub payer_data: Option<LnurlPayerDataRequest>,
+}
+
+/// The items of the LNURL-pay metadata, as per LUD-06. Items of unknown types are ignored.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct LnurlPayMetadata {
+    /// The `text/plain` description, always present in valid metadata
+    pub description: String,
+    /// The `text/long-desc` description
+    pub long_description: Option<String>,
+    /// The `image/png;base64` or `image/jpeg;base64` image
+    pub image: Option<LnurlPayImage>,
+    /// The `text/identifier` internet identifier, as per LUD-16
+    pub identifier: Option<String>,
+    /// The `text/email` email address, as per LUD-16
+    pub email: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct LnurlPayImage {
+    /// Either `image/png` or `image/jpeg`
+    pub mime_type: String,
+    /// The base64 encoded image
+    pub data: String,
+}
+
+/// The payer data fields a LNURL-pay endpoint asks for, as per LUD-18
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct LnurlPayerDataRequest {
+    #[serde(default)]
+    pub name: Option<LnurlPayerDataRequirement>,
+    #[serde(default)]
+    pub pubkey: Option<LnurlPayerDataRequirement>,
+    #[serde(default)]
+    pub identifier: Option<LnurlPayerDataRequirement>,
+    #[serde(default)]
+    pub email: Option<LnurlPayerDataRequirement>,
+    #[serde(default)]
+    pub auth: Option<LnurlPayerDataAuthRequirement>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct LnurlPayerDataRequirement {
+    #[serde(default)]
+    pub mandatory: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct LnurlPayerDataAuthRequirement {
+    #[serde(default)]
+    pub mandatory: bool,
+    /// Hex encoded 32 bytes of challenge, to be signed with the LNURL-auth linking key
+    pub k1: String,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -245,6 +368,56 @@ pub struct MessageSuccessActionData {
     pub message: String,
 }
 
+/// The steps taken to parse an input, explaining why it was recognized or rejected.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct ParseTrace {
+    pub events: Vec<ParseTraceEvent>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum ParseTraceEvent {
+    /// The parser recognized the input
+    Matched { parser: ParserKind },
+    /// The parser did not recognize the input
+    Rejected { parser: ParserKind, reason: String },
+    /// A DNS TXT lookup was made, returning the number of records found
+    DnsLookup {
+        name: String,
+        records: Option<u32>,
+        error: Option<String>,
+    },
+    /// An HTTP GET request was made
+    HttpRequest {
+        url: String,
+        status: Option<u16>,
+        error: Option<String>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum ParserKind {
+    Bip21,
+    Bip353,
+    Bitcoin,
+    Lightning,
+    LightningAddress,
+    Lnurl,
+}
+
+/// A BIP-78 or BIP-77 payjoin endpoint, see
+/// <https://github.com/bitcoin/bips/blob/master/bip-0078.mediawiki#bip21-payjoin-parameters>
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct Payjoin {
+    /// The URL payjoin proposals are sent to
+    pub endpoint: String,
+    /// Whether the receiver may substitute its output. Disabled with `pjos=0`.
+    pub output_substitution: bool,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
 pub enum PaymentScheme {
@@ -257,6 +430,9 @@ pub enum PaymentScheme {
 pub struct PaymentRequestSource {
     pub bip_21_uri: Option<String>,
     pub bip_353_address: Option<String>,
+    /// The serialized RFC 9102 DNSSEC proof of the BIP-353 address, which payees and auditors
+    /// can verify with [`crate::dns::verify_txt_proof`]
+    pub bip_353_dnssec_proof: Option<Vec<u8>>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -285,9 +461,23 @@ impl PaymentMethod {
             PaymentMethod::SilentPaymentAddress(_) => PaymentMethodType::SilentPaymentAddress,
         }
     }
+
+    /// The network the payment method was decoded for, if it is bound to one.
+    pub fn network(&self) -> Option<BitcoinNetwork> {
+        match self {
+            PaymentMethod::BitcoinAddress(address) => Some(address.network),
+            PaymentMethod::Bolt11Invoice(invoice) => Some(invoice.network),
+            PaymentMethod::LiquidAddress(address) => Some(address.network),
+            PaymentMethod::SilentPaymentAddress(address) => Some(address.network),
+            PaymentMethod::Bolt12Invoice(_)
+            | PaymentMethod::Bolt12Offer(_)
+            | PaymentMethod::LightningAddress(_)
+            | PaymentMethod::LnurlPay(_) => None,
+        }
+    }
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
 pub enum PaymentMethodType {
     BitcoinAddress,
@@ -311,8 +501,26 @@ pub enum ReceiveRequest {
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct SilentPaymentAddress {
     pub address: String,
+    /// The network derived from the address prefix. Testnet addresses (`tsp`) are shared by all
+    /// test networks, and are reported as [`BitcoinNetwork::Testnet3`].
     pub network: BitcoinNetwork,
+    /// The public key the receiver scans for payments with.
+    pub scan_pubkey: String,
     pub source: PaymentRequestSource,
+    /// The public key payments are made to, tweaked by the sender.
+    pub spend_pubkey: String,
+    pub version: u8,
+}
+
+impl SilentPaymentAddress {
+    /// Returns whether the address can be paid on the given network. Testnet addresses are shared
+    /// by all test networks, so they can be paid on any network other than mainnet.
+    pub fn is_valid_for_network(&self, network: BitcoinNetwork) -> bool {
+        match self.network {
+            BitcoinNetwork::Testnet3 => network != BitcoinNetwork::Bitcoin,
+            address_network => address_network == network,
+        }
+    }
 }
 
 /// [`SuccessAction`] where contents are ready to be consumed by the caller
@@ -1,4 +1,13 @@
-use bitcoin::{Address, Denomination, address::NetworkUnchecked};
+use std::fmt::Display;
+
+use bech32::{
+    Bech32, Bech32m, Fe32,
+    primitives::{
+        decode::{CheckedHrpstring, UncheckedHrpstring},
+        iter::Fe32IterExt,
+    },
+};
+use bitcoin::{Address, Denomination, address::NetworkUnchecked, secp256k1::PublicKey};
 use lightning::bolt11_invoice::Bolt11InvoiceDescriptionRef;
 use maybe_sync::{MaybeSend, MaybeSync};
 use percent_encoding_rfc3986::percent_decode_str;
@@ -6,29 +15,52 @@ use serde::Deserialize;
 use tracing::{debug, error};
 
 use crate::{
+    bolt12::invoice_request::{
+        DecodedInvoiceRequest, decode_invoice_request, offer_from_invoice_request,
+    },
     dns::{self, DnsResolver},
     error::{ServiceConnectivityError, ServiceConnectivityErrorKind},
     input::{Bip21Extra, ParseError, PaymentMethod, PaymentRequestSource, PaymentScheme},
     lnurl::{
         LnurlErrorData,
         auth::{self, LnurlAuthRequestData},
+        channel::{self, LnurlChannelRequestData},
         error::LnurlError,
+        pay::parse_metadata,
     },
+    network::BitcoinNetwork,
     rest::{ReqwestRestClient, RestClient},
 };
 
 use super::{
     Bip21, BitcoinAddress, Bolt11RouteHint, Bolt11RouteHintHop, Bolt12InvoiceRequest, Bolt12Offer,
     Bolt12OfferBlindedPath, DetailedBolt11Invoice, DetailedBolt12Invoice, DetailedBolt12Offer,
-    InputType, LightningAddress, LnurlPayRequest, LnurlWithdrawRequestData, ReceiveRequest,
-    SilentPaymentAddress, error::Bip21Error,
+    InputType, LightningAddress, LiquidAddress, LnurlPayRequest, LnurlWithdrawRequestData,
+    ParseTrace, ParseTraceEvent, ParserKind, Payjoin, ReceiveRequest, SilentPaymentAddress,
+    bip353::{bip353_dns_name, split_bip353_address},
+    error::Bip21Error,
+    liquid::{decode_liquid_address, is_lbtc_asset_id, is_liquid_hrp},
 };
 
 const BIP_21_PREFIX: &str = "bitcoin:";
-const BIP_353_USER_BITCOIN_PAYMENT_PREFIX: &str = "user._bitcoin-payment";
+const LIQUID_BIP_21_PREFIX: &str = "liquidnetwork:";
+const LIQUID_TESTNET_BIP_21_PREFIX: &str = "liquidtestnet:";
+/// Liquid asset ids are 32 byte hashes.
+const LIQUID_ASSET_ID_LEN: usize = 32;
 const LIGHTNING_PREFIX: &str = "lightning:";
 const LIGHTNING_PREFIX_LEN: usize = LIGHTNING_PREFIX.len();
 const LNURL_HRP: &str = "lnurl";
+/// BOLT12 strings are bech32 encoded without a checksum.
+const BOLT12_HRPS: [&str; 3] = ["lno", "lni", "lnr"];
+/// The currency prefixes of BOLT11 invoices, see
+/// <https://github.com/lightning/bolts/blob/master/11-payment-encoding.md#human-readable-part>
+const BOLT11_CURRENCY_PREFIXES: [&str; 5] = ["bc", "tb", "tbs", "bcrt", "sb"];
+const SEGWIT_HRPS: [&str; 3] = ["bc", "tb", "bcrt"];
+const SILENT_PAYMENT_HRP_MAINNET: &str = "sp";
+const SILENT_PAYMENT_HRP_REGTEST: &str = "sprt";
+const SILENT_PAYMENT_HRP_TESTNET: &str = "tsp";
+/// A compressed scan public key followed by a compressed spend public key.
+const SILENT_PAYMENT_KEYS_LEN: usize = 66;
 
 pub async fn parse(input: &str) -> Result<InputType, ParseError> {
     InputParser::new(dns::Resolver::new(), ReqwestRestClient::new()?)
@@ -36,9 +68,66 @@ pub async fn parse(input: &str) -> Result<InputType, ParseError> {
         .await
 }
 
+/// Parses the input, rejecting payment requests for another network than the given one. See
+/// [`InputParser::with_network`].
+pub async fn parse_for_network(
+    input: &str,
+    network: BitcoinNetwork,
+) -> Result<InputType, ParseError> {
+    InputParser::new(dns::Resolver::new(), ReqwestRestClient::new()?)
+        .with_network(network)
+        .parse(input)
+        .await
+}
+
+/// Parses the input, also returning a trace of how it was parsed. See
+/// [`InputParser::parse_with_trace`].
+pub async fn parse_with_trace(input: &str) -> (Result<InputType, ParseError>, ParseTrace) {
+    match ReqwestRestClient::new() {
+        Ok(rest_client) => {
+            InputParser::new(dns::Resolver::new(), rest_client)
+                .parse_with_trace(input)
+                .await
+        }
+        Err(err) => (Err(err.into()), ParseTrace::default()),
+    }
+}
+
 pub struct InputParser<C, D> {
     rest_client: C,
     dns_resolver: D,
+    network: Option<BitcoinNetwork>,
+}
+
+/// Records the parse trace when diagnostics are enabled.
+#[derive(Default)]
+struct Tracer {
+    trace: Option<ParseTrace>,
+}
+
+impl Tracer {
+    fn enabled() -> Self {
+        Self {
+            trace: Some(ParseTrace::default()),
+        }
+    }
+
+    fn record(&mut self, event: impl FnOnce() -> ParseTraceEvent) {
+        if let Some(trace) = &mut self.trace {
+            trace.events.push(event());
+        }
+    }
+
+    fn matched(&mut self, parser: ParserKind) {
+        self.record(|| ParseTraceEvent::Matched { parser });
+    }
+
+    fn rejected(&mut self, parser: ParserKind, reason: impl Display) {
+        self.record(|| ParseTraceEvent::Rejected {
+            parser,
+            reason: reason.to_string(),
+        });
+    }
 }
 
 impl<C, D> InputParser<C, D>
@@ -50,88 +139,164 @@ where
         InputParser {
             rest_client,
             dns_resolver,
+            network: None,
         }
     }
 
+    /// Sets the network payment requests are expected to be for. Payment requests containing a
+    /// payment method for another network are rejected with [`ParseError::InvalidNetwork`].
+    #[must_use]
+    pub fn with_network(mut self, network: BitcoinNetwork) -> Self {
+        self.network = Some(network);
+        self
+    }
+
     pub async fn parse(&self, input: &str) -> Result<InputType, ParseError> {
+        self.parse_traced(input, &mut Tracer::default()).await
+    }
+
+    /// Parses the input like [`InputParser::parse`], also returning a trace of the parsers that
+    /// were tried, the DNS and HTTP lookups made and why each parser rejected the input.
+    pub async fn parse_with_trace(
+        &self,
+        input: &str,
+    ) -> (Result<InputType, ParseError>, ParseTrace) {
+        let mut tracer = Tracer::enabled();
+        let result = self.parse_traced(input, &mut tracer).await;
+        (result, tracer.trace.unwrap_or_default())
+    }
+
+    async fn parse_traced(
+        &self,
+        input: &str,
+        tracer: &mut Tracer,
+    ) -> Result<InputType, ParseError> {
+        let input_type = self.parse_input(input, tracer).await?;
+        if let Some(network) = self.network {
+            check_network(&input_type, network)?;
+        }
+        Ok(input_type)
+    }
+
+    async fn parse_input(&self, input: &str, tracer: &mut Tracer) -> Result<InputType, ParseError> {
         let input = input.trim();
         if input.is_empty() {
             return Err(ParseError::EmptyInput);
         }
 
         if input.contains('@') {
-            if let Some(bip_21) = self.parse_bip_353(input).await? {
+            if let Some(bip_21) = self.parse_bip_353(input, tracer).await? {
+                tracer.matched(ParserKind::Bip353);
                 return Ok(InputType::PaymentRequest(PaymentScheme::Bip21(bip_21)));
             }
 
-            if let Some(lightning_address) = self.parse_lightning_address(input).await {
+            if let Some(lightning_address) = self.parse_lightning_address(input, tracer).await? {
+                tracer.matched(ParserKind::LightningAddress);
                 return Ok(InputType::PaymentRequest(PaymentScheme::PaymentMethod(
                     PaymentMethod::LightningAddress(lightning_address),
                 )));
             }
         }
 
-        if has_bip_21_prefix(input) {
+        if has_bip_21_prefix(input) || has_liquid_bip_21_prefix(input) {
             let source = PaymentRequestSource {
                 bip_21_uri: Some(input.to_string()),
-                bip_353_address: None,
+                ..Default::default()
             };
-            if let Some(bip_21) = parse_bip_21(input, &source)? {
-                return Ok(InputType::PaymentRequest(PaymentScheme::Bip21(bip_21)));
+            match parse_bip_21(input, &source) {
+                Ok(Some(bip_21)) => {
+                    tracer.matched(ParserKind::Bip21);
+                    return Ok(InputType::PaymentRequest(PaymentScheme::Bip21(bip_21)));
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    tracer.rejected(ParserKind::Bip21, &err);
+                    return Err(err.into());
+                }
             }
         }
 
         let source = PaymentRequestSource::default();
-        if let Some(input_type) = self.parse_lightning(input, &source).await? {
+        if let Some(input_type) = self.parse_lightning(input, &source, tracer).await? {
+            tracer.matched(ParserKind::Lightning);
             return Ok(input_type);
         }
+        tracer.rejected(
+            ParserKind::Lightning,
+            "not a lightning payment request or LNURL",
+        );
 
         if let Some(input_type) = parse_bitcoin(input, &source) {
+            tracer.matched(ParserKind::Bitcoin);
             return Ok(input_type);
         }
+        tracer.rejected(ParserKind::Bitcoin, "not a bitcoin or liquid address");
 
-        Err(ParseError::InvalidInput)
+        Err(invalid_input_error(input))
     }
 
-    async fn parse_bip_353(&self, input: &str) -> Result<Option<Bip21>, Bip21Error> {
-        // BIP-353 addresses may have a ₿ prefix, so strip it if present
-        let Some((local_part, domain)) = input.strip_prefix('₿').unwrap_or(input).split_once('@')
-        else {
+    async fn parse_bip_353(
+        &self,
+        input: &str,
+        tracer: &mut Tracer,
+    ) -> Result<Option<Bip21>, Bip21Error> {
+        let Some((local_part, domain)) = split_bip353_address(input) else {
             return Ok(None);
         };
 
         // Validate both parts are within the DNS label size limit.
         // See <https://datatracker.ietf.org/doc/html/rfc1035#section-2.3.4>
         if local_part.len() > 63 || domain.len() > 63 {
+            tracer.rejected(ParserKind::Bip353, "label exceeds 63 characters");
             return Ok(None);
         }
 
-        // Query for TXT records of a domain
-        let dns_name = format!("{local_part}.{BIP_353_USER_BITCOIN_PAYMENT_PREFIX}.{domain}");
-        let records = match self.dns_resolver.txt_lookup(dns_name).await {
-            Ok(records) => records,
+        // Query for TXT records of a domain, which BIP-353 requires to be DNSSEC signed
+        let dns_name = bip353_dns_name(local_part, domain);
+        let result = self
+            .dns_resolver
+            .verified_txt_lookup(dns_name.clone())
+            .await;
+        tracer.record(|| ParseTraceEvent::DnsLookup {
+            name: dns_name,
+            records: result
+                .as_ref()
+                .ok()
+                .map(|verified| u32::try_from(verified.records.len()).unwrap_or(u32::MAX)),
+            error: result.as_ref().err().map(ToString::to_string),
+        });
+        let verified = match result {
+            Ok(verified) => verified,
             Err(e) => {
-                debug!("No BIP353 TXT records found: {}", e);
+                debug!("No verified BIP353 TXT records found: {}", e);
+                tracer.rejected(ParserKind::Bip353, "no DNSSEC proven TXT records found");
                 return Ok(None);
             }
         };
 
-        let Some(bip_21) = extract_bip353_record(records) else {
+        let Some(bip_21) = extract_bip353_record(verified.records) else {
+            tracer.rejected(ParserKind::Bip353, "no single bitcoin: TXT record found");
             return Ok(None);
         };
-        parse_bip_21(
+        let result = parse_bip_21(
             &bip_21,
             &PaymentRequestSource {
                 bip_21_uri: Some(bip_21.clone()),
                 bip_353_address: Some(input.to_string()),
+                bip_353_dnssec_proof: Some(verified.proof),
             },
-        )
+        );
+        if let Err(err) = &result {
+            tracer.rejected(ParserKind::Bip353, err);
+        }
+        result
     }
 
     async fn parse_lightning(
         &self,
         input: &str,
         source: &PaymentRequestSource,
+        tracer: &mut Tracer,
     ) -> Result<Option<InputType>, ParseError> {
         let input = if has_lightning_prefix(input) {
             &input[LIGHTNING_PREFIX_LEN..]
@@ -151,19 +316,27 @@ where
             )));
         }
 
-        if let Some(lnurl) = self.parse_lnurl(input, source).await? {
-            return Ok(Some(lnurl));
+        match self.parse_lnurl(input, source, tracer).await {
+            Ok(Some(lnurl)) => return Ok(Some(lnurl)),
+            Ok(None) => {}
+            Err(err) => {
+                tracer.rejected(ParserKind::Lnurl, &err);
+                return Err(err.into());
+            }
         }
 
         Ok(None)
     }
 
-    async fn parse_lightning_address(&self, input: &str) -> Option<LightningAddress> {
-        if !input.contains('@') {
-            return None;
-        }
-
-        let (user, domain) = input.strip_prefix('₿').unwrap_or(input).split_once('@')?;
+    async fn parse_lightning_address(
+        &self,
+        input: &str,
+        tracer: &mut Tracer,
+    ) -> Result<Option<LightningAddress>, ParseError> {
+        let Some((user, domain)) = input.strip_prefix('₿').unwrap_or(input).split_once('@')
+        else {
+            return Ok(None);
+        };
 
         // It is safe to downcase the domains since they are case-insensitive.
         // https://www.rfc-editor.org/rfc/rfc3986#section-3.2.2
@@ -173,7 +346,8 @@ where
             .chars()
             .all(|c| c.is_alphanumeric() || ['-', '_', '.'].contains(&c))
         {
-            return None;
+            tracer.rejected(ParserKind::LightningAddress, "invalid user name");
+            return Ok(None);
         }
 
         let scheme = if has_extension(&domain, "onion") {
@@ -184,30 +358,41 @@ where
 
         let Ok(url) = reqwest::Url::parse(&format!("{scheme}{domain}/.well-known/lnurlp/{user}"))
         else {
-            return None;
+            tracer.rejected(ParserKind::LightningAddress, "invalid domain");
+            return Ok(None);
         };
 
-        let input_type = self
-            .resolve_lnurl(&url, &PaymentRequestSource::default())
+        let input_type = match self
+            .resolve_lnurl(&url, &PaymentRequestSource::default(), tracer)
             .await
-            .ok()?;
-
-        let address = format!("{user}@{domain}");
-        match input_type {
-            InputType::PaymentRequest(PaymentScheme::PaymentMethod(PaymentMethod::LnurlPay(
-                pay_request,
-            ))) => Some(LightningAddress {
-                address,
-                pay_request,
-            }),
-            _ => None, // TODO: log or return error.
-        }
+        {
+            Ok(input_type) => input_type,
+            Err(err) => {
+                tracer.rejected(ParserKind::LightningAddress, &err);
+                return Err(ParseError::LightningAddressResolution(err));
+            }
+        };
+
+        let InputType::PaymentRequest(PaymentScheme::PaymentMethod(PaymentMethod::LnurlPay(
+            pay_request,
+        ))) = input_type
+        else {
+            tracer.rejected(ParserKind::LightningAddress, "not an LNURL-pay endpoint");
+            return Err(ParseError::LightningAddressResolution(LnurlError::General(
+                "not an LNURL-pay endpoint".to_string(),
+            )));
+        };
+        Ok(Some(LightningAddress {
+            address: format!("{user}@{domain}"),
+            pay_request,
+        }))
     }
 
     async fn parse_lnurl(
         &self,
         input: &str,
         source: &PaymentRequestSource,
+        tracer: &mut Tracer,
     ) -> Result<Option<InputType>, LnurlError> {
         let mut input = match bech32::decode(input) {
             Ok((hrp, data)) => {
@@ -224,7 +409,7 @@ where
             Err(_) => input.to_string(),
         };
 
-        let supported_prefixes: [&str; 3] = ["lnurlp", "lnurlw", "keyauth"];
+        let supported_prefixes: [&str; 4] = ["lnurlc", "lnurlp", "lnurlw", "keyauth"];
 
         // Treat prefix: and prefix:// the same, to cover both vendor implementations
         // https://github.com/lnbits/lnbits/pull/762#issue-1309702380
@@ -279,13 +464,14 @@ where
             &_ => return Err(LnurlError::UnknownScheme), // TODO: log or return error.
         }
 
-        Ok(Some(self.resolve_lnurl(&url, source).await?))
+        Ok(Some(self.resolve_lnurl(&url, source, tracer).await?))
     }
 
     async fn resolve_lnurl(
         &self,
         url: &reqwest::Url,
         _source: &PaymentRequestSource,
+        tracer: &mut Tracer,
     ) -> Result<InputType, LnurlError> {
         if let Some(query) = url.query() {
             if query.contains("tag=login") {
@@ -294,11 +480,13 @@ where
             }
         }
 
-        let (response, _) = self
-            .rest_client
-            .get(url.as_ref())
-            .await
-            .map_err(LnurlError::ServiceConnectivity)?;
+        let result = self.rest_client.get(url.as_ref()).await;
+        tracer.record(|| ParseTraceEvent::HttpRequest {
+            url: url.to_string(),
+            status: result.as_ref().ok().map(|(_, status)| *status),
+            error: result.as_ref().err().map(ToString::to_string),
+        });
+        let (response, _) = result.map_err(LnurlError::ServiceConnectivity)?;
         let lnurl_data: LnurlRequestData =
             parse_json(&response).map_err(LnurlError::ServiceConnectivity)?;
         let domain = url.host().ok_or(LnurlError::MissingDomain)?.to_string();
@@ -307,12 +495,16 @@ where
                 PaymentScheme::PaymentMethod(PaymentMethod::LnurlPay(LnurlPayRequest {
                     domain,
                     url: url.to_string(),
+                    metadata: parse_metadata(&data.metadata_str)?,
                     ..data
                 })),
             ),
             LnurlRequestData::WithdrawRequest { data } => {
                 InputType::ReceiveRequest(ReceiveRequest::LnurlWithdraw(data))
             }
+            LnurlRequestData::ChannelRequest { data } => {
+                InputType::LnurlChannel(channel::validate_request(data, domain)?)
+            }
             LnurlRequestData::AuthRequest { data } => InputType::LnurlAuth(data),
             LnurlRequestData::Error { data } => return Err(LnurlError::EndpointError(data.reason)),
         })
@@ -326,10 +518,116 @@ fn format_short_channel_id(id: u64) -> String {
     format!("{block_num}x{tx_num}x{tx_out}")
 }
 
-fn has_bip_21_prefix(input: &str) -> bool {
+/// Ensures the payment methods of a payment request are valid for the expected network.
+fn check_network(input_type: &InputType, network: BitcoinNetwork) -> Result<(), ParseError> {
+    let payment_methods = match input_type {
+        InputType::PaymentRequest(PaymentScheme::Bip21(bip_21)) => {
+            bip_21.payment_methods.iter().collect()
+        }
+        InputType::PaymentRequest(PaymentScheme::PaymentMethod(payment_method)) => {
+            vec![payment_method]
+        }
+        _ => Vec::new(),
+    };
+
+    for payment_method in payment_methods {
+        let is_valid = match payment_method {
+            PaymentMethod::BitcoinAddress(address) => address.is_valid_for_network(network),
+            _ => payment_method
+                .network()
+                .is_none_or(|decoded| is_same_network(decoded, network)),
+        };
+        if !is_valid {
+            let decoded = payment_method.network().unwrap_or(network);
+            return Err(ParseError::InvalidNetwork(decoded));
+        }
+    }
+    Ok(())
+}
+
+/// The test networks share the encoding of their invoices and addresses, so a payment method
+/// decoded for one test network is also valid for the others.
+fn is_same_network(decoded: BitcoinNetwork, expected: BitcoinNetwork) -> bool {
+    let is_test_network = |network| {
+        matches!(
+            network,
+            BitcoinNetwork::Testnet3 | BitcoinNetwork::Testnet4 | BitcoinNetwork::Signet
+        )
+    };
+    decoded == expected || (is_test_network(decoded) && is_test_network(expected))
+}
+
+/// Explains why no parser accepted the input. Bech32 strings with a known human-readable part
+/// but an invalid checksum are reported as [`ParseError::InvalidChecksum`], valid bech32
+/// strings with an unknown human-readable part as [`ParseError::UnsupportedHrp`].
+fn invalid_input_error(input: &str) -> ParseError {
+    let input = if has_lightning_prefix(input) {
+        &input[LIGHTNING_PREFIX_LEN..]
+    } else {
+        input
+    };
+    let Ok(hrpstring) = UncheckedHrpstring::new(input) else {
+        return ParseError::InvalidInput;
+    };
+
+    let hrp = hrpstring.hrp().to_lowercase();
+    if BOLT12_HRPS.contains(&hrp.as_str()) {
+        return ParseError::InvalidInput;
+    }
+
+    let has_valid_checksum =
+        hrpstring.has_valid_checksum::<Bech32>() || hrpstring.has_valid_checksum::<Bech32m>();
+    match (is_supported_hrp(&hrp), has_valid_checksum) {
+        (true, false) => ParseError::InvalidChecksum,
+        (false, true) => ParseError::UnsupportedHrp(hrp),
+        _ => ParseError::InvalidInput,
+    }
+}
+
+/// Returns whether the lowercase human-readable part belongs to a checksummed bech32 string
+/// the parser supports.
+fn is_supported_hrp(hrp: &str) -> bool {
+    if hrp == LNURL_HRP
+        || SEGWIT_HRPS.contains(&hrp)
+        || is_liquid_hrp(hrp)
+        || [
+            SILENT_PAYMENT_HRP_MAINNET,
+            SILENT_PAYMENT_HRP_TESTNET,
+            SILENT_PAYMENT_HRP_REGTEST,
+        ]
+        .contains(&hrp)
+    {
+        return true;
+    }
+
+    // BOLT11 invoices have the currency prefix followed by an optional amount
+    let Some(currency_and_amount) = hrp.strip_prefix("ln") else {
+        return false;
+    };
+    BOLT11_CURRENCY_PREFIXES.iter().any(|currency| {
+        currency_and_amount
+            .strip_prefix(currency)
+            .is_some_and(is_bolt11_amount)
+    })
+}
+
+fn is_bolt11_amount(amount: &str) -> bool {
+    if amount.is_empty() {
+        return true;
+    }
+
+    let digits = amount.strip_suffix(['m', 'u', 'n', 'p']).unwrap_or(amount);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+pub(super) fn has_bip_21_prefix(input: &str) -> bool {
     has_prefix(input, BIP_21_PREFIX)
 }
 
+fn has_liquid_bip_21_prefix(input: &str) -> bool {
+    has_prefix(input, LIQUID_BIP_21_PREFIX) || has_prefix(input, LIQUID_TESTNET_BIP_21_PREFIX)
+}
+
 fn has_extension(input: &str, extension: &str) -> bool {
     std::path::Path::new(input)
         .extension()
@@ -374,15 +672,23 @@ fn extract_bip353_record(records: Vec<String>) -> Option<String> {
     bip353_record.into_iter().next()
 }
 
-fn parse_bip_21(input: &str, source: &PaymentRequestSource) -> Result<Option<Bip21>, Bip21Error> {
-    // TODO: Support liquid BIP-21
-    if !has_bip_21_prefix(input) {
+pub(super) fn parse_bip_21(
+    input: &str,
+    source: &PaymentRequestSource,
+) -> Result<Option<Bip21>, Bip21Error> {
+    // Liquid URIs follow the same format, with the network determined by the scheme.
+    let (prefix, liquid_network) = if has_bip_21_prefix(input) {
+        (BIP_21_PREFIX, None)
+    } else if has_prefix(input, LIQUID_BIP_21_PREFIX) {
+        (LIQUID_BIP_21_PREFIX, Some(BitcoinNetwork::Bitcoin))
+    } else if has_prefix(input, LIQUID_TESTNET_BIP_21_PREFIX) {
+        (LIQUID_TESTNET_BIP_21_PREFIX, Some(BitcoinNetwork::Testnet3))
+    } else {
         return Ok(None);
-    }
+    };
 
-    println!("{input}");
     let uri = input.to_string();
-    let input = &input[BIP_21_PREFIX.len()..];
+    let input = &input[prefix.len()..];
     let mut bip_21 = Bip21 {
         uri,
         ..Default::default()
@@ -393,9 +699,16 @@ fn parse_bip_21(input: &str, source: &PaymentRequestSource) -> Result<Option<Bip
         None => (input, None),
     };
 
-    println!("{input} - {address}, {params:?}");
-
-    if !address.is_empty() {
+    if let Some(expected_network) = liquid_network {
+        if !address.is_empty() {
+            let liquid_address = parse_liquid_address(address, source)
+                .filter(|liquid_address| liquid_address.network == expected_network)
+                .ok_or(Bip21Error::InvalidAddress)?;
+            bip_21
+                .payment_methods
+                .push(PaymentMethod::LiquidAddress(liquid_address));
+        }
+    } else if !address.is_empty() {
         let address: Address<NetworkUnchecked> =
             address.parse().map_err(|_| Bip21Error::InvalidAddress)?;
         let network = match 1 {
@@ -420,10 +733,13 @@ fn parse_bip_21(input: &str, source: &PaymentRequestSource) -> Result<Option<Bip
             .push(PaymentMethod::BitcoinAddress(BitcoinAddress {
                 address: address.assume_checked().to_string(),
                 network,
+                payjoin: None,
                 source: source.clone(),
             }));
     }
 
+    let mut amount = None;
+    let mut output_substitution = None;
     if let Some(params) = params {
         for param in params.split('&') {
             let pos = param.find('=').ok_or(Bip21Error::MissingEquals)?;
@@ -437,20 +753,21 @@ fn parse_bip_21(input: &str, source: &PaymentRequestSource) -> Result<Option<Bip
             };
 
             match key {
-                "amount" if bip_21.amount_sat.is_some() => {
+                "amount" if amount.is_some() => {
                     return Err(Bip21Error::multiple_params(key));
                 }
-                "amount" => {
-                    bip_21.amount_sat = Some(
-                        bitcoin::Amount::from_str_in(value, Denomination::Bitcoin)
-                            .map_err(|_| Bip21Error::InvalidAmount)?
-                            .to_sat(),
-                    );
-                }
+                "amount" => amount = Some(value),
                 "assetid" if bip_21.asset_id.is_some() => {
                     return Err(Bip21Error::multiple_params(key));
                 }
-                "assetid" => bip_21.asset_id = Some(value.to_string()),
+                "assetid" => {
+                    let is_valid = hex::decode(value)
+                        .is_ok_and(|asset_id| asset_id.len() == LIQUID_ASSET_ID_LEN);
+                    if !is_valid {
+                        return Err(Bip21Error::invalid_parameter("assetid"));
+                    }
+                    bip_21.asset_id = Some(value.to_lowercase());
+                }
                 "bc" => {}
                 "label" if bip_21.label.is_some() => {
                     return Err(Bip21Error::multiple_params(key));
@@ -494,8 +811,29 @@ fn parse_bip_21(input: &str, source: &PaymentRequestSource) -> Result<Option<Bip
                             .to_string(),
                     );
                 }
+                "pj" if bip_21.payjoin.is_some() => {
+                    return Err(Bip21Error::multiple_params(key));
+                }
+                "pj" => {
+                    let endpoint = parse_payjoin_endpoint(value)
+                        .ok_or_else(|| Bip21Error::invalid_parameter("pj"))?;
+                    bip_21.payjoin = Some(Payjoin {
+                        endpoint,
+                        output_substitution: true,
+                    });
+                }
+                "pjos" if output_substitution.is_some() => {
+                    return Err(Bip21Error::multiple_params(key));
+                }
+                "pjos" => {
+                    output_substitution = match value {
+                        "0" => Some(false),
+                        "1" => Some(true),
+                        _ => return Err(Bip21Error::invalid_parameter("pjos")),
+                    };
+                }
                 "sp" => {
-                    let silent_payment_address = parse_silent_payment_address(input, source);
+                    let silent_payment_address = parse_silent_payment_address(value, source);
                     match silent_payment_address {
                         Some(silent_payment) => bip_21
                             .payment_methods
@@ -521,22 +859,107 @@ fn parse_bip_21(input: &str, source: &PaymentRequestSource) -> Result<Option<Bip
         return Err(Bip21Error::NoPaymentMethods);
     }
 
+    // Liquid amounts are only meaningful for a specific asset. Amounts of other assets than
+    // L-BTC are not denominated in bitcoin, so they are kept out of `amount_sat`.
+    if let Some(amount) = amount {
+        match (liquid_network, &bip_21.asset_id) {
+            (Some(_), None) => return Err(Bip21Error::MissingAssetId),
+            (Some(network), Some(asset_id)) if !is_lbtc_asset_id(asset_id, network) => {
+                if !is_decimal_amount(amount) {
+                    return Err(Bip21Error::InvalidAmount);
+                }
+                bip_21.asset_amount = Some(amount.to_string());
+            }
+            _ => {
+                bip_21.amount_sat = Some(
+                    bitcoin::Amount::from_str_in(amount, Denomination::Bitcoin)
+                        .map_err(|_| Bip21Error::InvalidAmount)?
+                        .to_sat(),
+                );
+            }
+        }
+    }
+
+    // The requested asset applies to the Liquid address, so callers can refuse assets they
+    // cannot pay.
+    if let Some(asset_id) = &bip_21.asset_id {
+        for payment_method in &mut bip_21.payment_methods {
+            if let PaymentMethod::LiquidAddress(address) = payment_method {
+                address.asset_id = Some(asset_id.clone());
+            }
+        }
+    }
+
+    // Payjoin proposals are made for the on-chain address, so they require one.
+    if let Some(payjoin) = &mut bip_21.payjoin {
+        payjoin.output_substitution = output_substitution.unwrap_or(true);
+        let mut has_address = false;
+        for payment_method in &mut bip_21.payment_methods {
+            if let PaymentMethod::BitcoinAddress(address) = payment_method {
+                address.payjoin = Some(payjoin.clone());
+                has_address = true;
+            }
+        }
+        if !has_address {
+            return Err(Bip21Error::invalid_parameter("pj"));
+        }
+    }
+
+    // An embedded invoice asking for a different amount makes it ambiguous what is to be paid.
+    if let Some(amount_sat) = bip_21.amount_sat {
+        let amount_msat = amount_sat.saturating_mul(1_000);
+        for payment_method in &bip_21.payment_methods {
+            if let PaymentMethod::Bolt11Invoice(DetailedBolt11Invoice {
+                amount_msat: Some(invoice_amount_msat),
+                ..
+            }) = payment_method
+                && *invoice_amount_msat != amount_msat
+            {
+                return Err(Bip21Error::AmountMismatch {
+                    amount_msat,
+                    invoice_amount_msat: *invoice_amount_msat,
+                });
+            }
+        }
+    }
+
     Ok(Some(bip_21))
 }
 
+/// Returns whether the amount is a non-negative decimal number, like `12` or `0.5`.
+fn is_decimal_amount(amount: &str) -> bool {
+    let (integer, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    !integer.is_empty()
+        && integer.bytes().all(|b| b.is_ascii_digit())
+        && fraction.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Parses the percent encoded payjoin endpoint. It has to be served over https, or over http
+/// when it is an onion service.
+fn parse_payjoin_endpoint(value: &str) -> Option<String> {
+    let endpoint = percent_decode_str(value).ok()?.decode_utf8().ok()?;
+    let url = reqwest::Url::parse(&endpoint).ok()?;
+    let is_onion = url
+        .host_str()
+        .is_some_and(|host| has_extension(host, "onion"));
+    match url.scheme() {
+        "https" => Some(endpoint.to_string()),
+        "http" if is_onion => Some(endpoint.to_string()),
+        _ => None,
+    }
+}
+
 fn parse_bitcoin(input: &str, source: &PaymentRequestSource) -> Option<InputType> {
-    if let Ok((hrp, _)) = bech32::decode(input) {
-        if hrp.to_lowercase().as_str() == "sp" {
-            match parse_silent_payment_address(input, source) {
-                Some(silent_payment) => {
-                    return Some(InputType::PaymentRequest(PaymentScheme::PaymentMethod(
-                        PaymentMethod::SilentPaymentAddress(silent_payment),
-                    )));
-                }
-                None => {
-                    return None;
-                }
-            }
+    if let Ok(hrp) = CheckedHrpstring::new::<Bech32m>(input).map(|checked| checked.hrp()) {
+        if matches!(
+            hrp.to_lowercase().as_str(),
+            SILENT_PAYMENT_HRP_MAINNET | SILENT_PAYMENT_HRP_TESTNET | SILENT_PAYMENT_HRP_REGTEST
+        ) {
+            return parse_silent_payment_address(input, source).map(|silent_payment| {
+                InputType::PaymentRequest(PaymentScheme::PaymentMethod(
+                    PaymentMethod::SilentPaymentAddress(silent_payment),
+                ))
+            });
         }
     }
 
@@ -546,6 +969,12 @@ fn parse_bitcoin(input: &str, source: &PaymentRequestSource) -> Option<InputType
         )));
     }
 
+    if let Some(address) = parse_liquid_address(input, source) {
+        return Some(InputType::PaymentRequest(PaymentScheme::PaymentMethod(
+            PaymentMethod::LiquidAddress(address),
+        )));
+    }
+
     None
 }
 
@@ -567,6 +996,17 @@ fn parse_bitcoin_address(input: &str, source: &PaymentRequestSource) -> Option<B
     Some(BitcoinAddress {
         address: address.assume_checked().to_string(),
         network,
+        payjoin: None,
+        source: source.clone(),
+    })
+}
+
+fn parse_liquid_address(input: &str, source: &PaymentRequestSource) -> Option<LiquidAddress> {
+    let network = decode_liquid_address(input)?;
+    Some(LiquidAddress {
+        address: input.to_string(),
+        asset_id: None,
+        network,
         source: source.clone(),
     })
 }
@@ -676,11 +1116,42 @@ fn parse_bolt12_invoice(
 }
 
 fn parse_bolt12_invoice_request(
-    _input: &str,
-    _source: &PaymentRequestSource,
+    input: &str,
+    source: &PaymentRequestSource,
 ) -> Option<Bolt12InvoiceRequest> {
-    // TODO: Implement parsing of Bolt12 invoice requests
-    None
+    let Ok(invoice_request) = decode_invoice_request(input) else {
+        return None;
+    };
+
+    match invoice_request {
+        DecodedInvoiceRequest::Refund(refund) => Some(Bolt12InvoiceRequest {
+            absolute_expiry: refund.absolute_expiry().map(|e| e.as_secs()),
+            amount_msat: Some(refund.amount_msats()),
+            chain: refund.chain().to_string(),
+            description: Some(refund.description().to_string()),
+            invoice_request: input.to_string(),
+            offer: None,
+            payer_note: refund.payer_note().map(|n| n.to_string()),
+            payer_signing_pubkey: refund.payer_signing_pubkey().to_string(),
+            quantity: refund.quantity(),
+            source: source.clone(),
+        }),
+        DecodedInvoiceRequest::InvoiceRequest(invoice_request) => {
+            let offer = offer_from_invoice_request(&invoice_request).ok()?;
+            Some(Bolt12InvoiceRequest {
+                absolute_expiry: None,
+                amount_msat: invoice_request.amount_msats(),
+                chain: invoice_request.chain().to_string(),
+                description: None,
+                invoice_request: input.to_string(),
+                offer: Some(parse_bolt12_offer(&offer.to_string(), source)?),
+                payer_note: invoice_request.payer_note().map(|n| n.to_string()),
+                payer_signing_pubkey: invoice_request.payer_signing_pubkey().to_string(),
+                quantity: invoice_request.quantity(),
+                source: source.clone(),
+            })
+        }
+    }
 }
 
 pub fn parse_json<T>(json: &str) -> Result<T, ServiceConnectivityError>
@@ -717,12 +1188,50 @@ fn parse_lightning_payment_method(
     None
 }
 
+/// Parses a BIP-352 silent payment address, see
+/// <https://github.com/bitcoin/bips/blob/master/bip-0352.mediawiki#address-encoding>
 fn parse_silent_payment_address(
-    _input: &str,
-    _source: &PaymentRequestSource,
+    input: &str,
+    source: &PaymentRequestSource,
 ) -> Option<SilentPaymentAddress> {
-    // TODO: Support silent payment addresses
-    None
+    let checked = CheckedHrpstring::new::<Bech32m>(input).ok()?;
+    let network = match checked.hrp().to_lowercase().as_str() {
+        SILENT_PAYMENT_HRP_MAINNET => BitcoinNetwork::Bitcoin,
+        SILENT_PAYMENT_HRP_TESTNET => BitcoinNetwork::Testnet3,
+        SILENT_PAYMENT_HRP_REGTEST => BitcoinNetwork::Regtest,
+        _ => return None,
+    };
+
+    let mut data = checked
+        .data_part_ascii_no_checksum()
+        .iter()
+        .map(|c| Fe32::from_char(char::from(*c)));
+    let version = data.next()?.ok()?.to_u8();
+    let payload: Vec<u8> = data
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?
+        .into_iter()
+        .fes_to_bytes()
+        .collect();
+    let keys = match version {
+        // Version 0 addresses contain exactly the scan and spend keys.
+        0 if payload.len() == SILENT_PAYMENT_KEYS_LEN => &payload[..],
+        // Future versions must remain backwards compatible, so only the keys are read.
+        1..=30 => payload.get(..SILENT_PAYMENT_KEYS_LEN)?,
+        _ => return None,
+    };
+
+    let (scan_pubkey, spend_pubkey) = keys.split_at(SILENT_PAYMENT_KEYS_LEN / 2);
+    let scan_pubkey = PublicKey::from_slice(scan_pubkey).ok()?;
+    let spend_pubkey = PublicKey::from_slice(spend_pubkey).ok()?;
+    Some(SilentPaymentAddress {
+        address: input.to_string(),
+        network,
+        scan_pubkey: scan_pubkey.to_string(),
+        source: source.clone(),
+        spend_pubkey: spend_pubkey.to_string(),
+        version,
+    })
 }
 
 #[derive(Deserialize, Debug)]
@@ -737,6 +1246,10 @@ pub enum LnurlRequestData {
         #[serde(flatten)]
         data: LnurlWithdrawRequestData,
     },
+    ChannelRequest {
+        #[serde(flatten)]
+        data: LnurlChannelRequestData,
+    },
     #[serde(rename = "login")]
     AuthRequest {
         #[serde(flatten)]
@@ -751,13 +1264,22 @@ pub enum LnurlRequestData {
 #[cfg(test)]
 mod tests {
 
+    use bech32::{
+        Bech32m, Fe32, Hrp,
+        primitives::iter::{ByteIterExt, Fe32IterExt},
+    };
+    use bitcoin::secp256k1::{Keypair, Secp256k1, SecretKey};
+    use lightning::offers::refund::RefundBuilder;
     use serde_json::json;
 
     use crate::input::error::Bip21Error;
     use crate::input::parser::InputParser;
     use crate::input::{
-        Bip21, Bip21Extra, BitcoinAddress, InputType, ParseError, PaymentMethod, PaymentScheme,
+        Bip21, Bip21Extra, BitcoinAddress, InputType, ParseError, ParseTraceEvent, ParserKind,
+        Payjoin, PaymentMethod, PaymentScheme, ReceiveRequest,
     };
+    use crate::lnurl::error::LnurlError;
+    use crate::network::BitcoinNetwork;
     use crate::test_utils::mock_dns_resolver::MockDnsResolver;
     use crate::test_utils::mock_rest_client::{MockResponse, MockRestClient};
 
@@ -894,6 +1416,60 @@ mod tests {
         ));
     }
 
+    #[breez_sdk_macros::async_test_all]
+    async fn test_bip21_with_payjoin() {
+        let mock_dns_resolver = MockDnsResolver::new();
+        let mock_rest_client = MockRestClient::new();
+        let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client);
+
+        let addr = "1andreas3batLhQa2FawWjeyjCqyBzypd";
+
+        for (bip21, endpoint, output_substitution) in [
+            (
+                format!("bitcoin:{addr}?pj=https://example.com/pj"),
+                "https://example.com/pj",
+                true,
+            ),
+            (
+                format!("bitcoin:{addr}?pjos=0&req-pj=https%3A%2F%2Fexample.com%2Fpj"),
+                "https://example.com/pj",
+                false,
+            ),
+            (
+                format!("bitcoin:{addr}?pj=http://example.onion/pj&pjos=1"),
+                "http://example.onion/pj",
+                true,
+            ),
+        ] {
+            let result = input_parser.parse(&bip21).await;
+            let Ok(InputType::PaymentRequest(PaymentScheme::Bip21(bip21))) = result else {
+                panic!("Expected BIP21, got {result:?}");
+            };
+            let expected = Payjoin {
+                endpoint: endpoint.to_string(),
+                output_substitution,
+            };
+            assert_eq!(bip21.payjoin.as_ref(), Some(&expected));
+            assert!(matches!(
+                &bip21.payment_methods[0],
+                PaymentMethod::BitcoinAddress(address) if address.payjoin.as_ref() == Some(&expected)
+            ));
+        }
+
+        for bip21 in [
+            format!("bitcoin:{addr}?pj=http://example.com/pj"),
+            format!("bitcoin:{addr}?pj=not-a-url"),
+            format!("bitcoin:{addr}?pj=https://example.com/pj&pjos=2"),
+            format!("bitcoin:{addr}?pj=https://example.com/a&pj=https://example.com/b"),
+        ] {
+            let result = input_parser.parse(&bip21).await;
+            assert!(
+                matches!(result, Err(ParseError::Bip21Error(_))),
+                "{bip21}: {result:?}"
+            );
+        }
+    }
+
     #[breez_sdk_macros::async_test_all]
     async fn test_bip21_with_extra_parameters() {
         let mock_dns_resolver = MockDnsResolver::new();
@@ -930,6 +1506,43 @@ mod tests {
         assert!(matches!(result, Err(ParseError::Bip21Error(_))));
     }
 
+    #[breez_sdk_macros::async_test_all]
+    async fn test_bip21_with_invoice_amount() {
+        let mock_dns_resolver = MockDnsResolver::new();
+        let mock_rest_client = MockRestClient::new();
+        let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client);
+
+        let addr = "1andreas3batLhQa2FawWjeyjCqyBzypd";
+        let bolt11 = "lnbc110n1p38q3gtpp5ypz09jrd8p993snjwnm68cph4ftwp22le34xd4r8ftspwshxhmnsdqqxqyjw5qcqpxsp5htlg8ydpywvsa7h3u4hdn77ehs4z4e844em0apjyvmqfkzqhhd2q9qgsqqqyssqszpxzxt9uuqzymr7zxcdccj5g69s8q7zzjs7sgxn9ejhnvdh6gqjcy22mss2yexunagm5r2gqczh8k24cwrqml3njskm548aruhpwssq9nvrvz";
+
+        // The invoice is for 11 sat
+        let result = input_parser
+            .parse(&format!(
+                "bitcoin:{addr}?amount=0.00000011&lightning={bolt11}"
+            ))
+            .await;
+        assert!(matches!(
+            result,
+            Ok(InputType::PaymentRequest(PaymentScheme::Bip21(Bip21 {
+                amount_sat: Some(11),
+                ..
+            })))
+        ));
+
+        let result = input_parser
+            .parse(&format!(
+                "bitcoin:{addr}?lightning={bolt11}&amount=0.00000012"
+            ))
+            .await;
+        assert!(matches!(
+            result,
+            Err(ParseError::Bip21Error(Bip21Error::AmountMismatch {
+                amount_msat: 12_000,
+                invoice_amount_msat: 11_000
+            }))
+        ));
+    }
+
     #[breez_sdk_macros::async_test_all]
     async fn test_bip21_with_invalid_lightning() {
         let mock_dns_resolver = MockDnsResolver::new();
@@ -1018,7 +1631,10 @@ mod tests {
         let result = input_parser.parse(bip353_address).await;
 
         // Should fail to parse the BIP353 record and fall back to checking if it's a lightning address
-        assert!(matches!(result, Err(ParseError::InvalidInput)));
+        assert!(matches!(
+            result,
+            Err(ParseError::LightningAddressResolution(_))
+        ));
     }
 
     #[breez_sdk_macros::async_test_all]
@@ -1041,17 +1657,110 @@ mod tests {
     }
 
     #[breez_sdk_macros::async_test_all]
-    async fn test_bip353_address_too_long() {
+    async fn test_bip353_address_source() {
         let mock_dns_resolver = MockDnsResolver::new();
+        let addr = "1andreas3batLhQa2FawWjeyjCqyBzypd";
+        mock_dns_resolver.add_response(vec![format!("bitcoin:{addr}")]);
         let mock_rest_client = MockRestClient::new();
         let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client);
 
-        // Local part longer than 63 chars
-        let too_long_local = "a".repeat(64) + "@example.com";
-        let result = input_parser.parse(&too_long_local).await;
-
-        // Should not be recognized as a BIP353 address
-        assert!(!matches!(
+        // The source carries the address and the DNSSEC proof of its TXT record
+        let result = input_parser.parse("user@bitcoin-domain.com").await;
+        assert!(matches!(
+            result,
+            Ok(InputType::PaymentRequest(PaymentScheme::Bip21(Bip21 { payment_methods, .. })))
+            if matches!(
+                &payment_methods[..],
+                [PaymentMethod::BitcoinAddress(BitcoinAddress { address, source, .. })]
+                if address == addr
+                    && source.bip_353_address.as_deref() == Some("user@bitcoin-domain.com")
+                    && source.bip_353_dnssec_proof == Some(Vec::new())
+            )
+        ));
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_parse_with_trace() {
+        let mock_dns_resolver = MockDnsResolver::new();
+        mock_dns_resolver.add_response(vec![String::from("not-a-valid-bip21-uri")]);
+        let mock_rest_client = MockRestClient::new();
+        mock_rest_client.add_response(MockResponse::new(404, "Not found".to_string()));
+        let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client);
+
+        let (result, trace) = input_parser.parse_with_trace("test@example.com").await;
+        assert!(matches!(
+            result,
+            Err(ParseError::LightningAddressResolution(_))
+        ));
+        assert_eq!(trace.events.len(), 4, "{trace:?}");
+        assert_eq!(
+            trace.events[0],
+            ParseTraceEvent::DnsLookup {
+                name: "test.user._bitcoin-payment.example.com".to_string(),
+                records: Some(1),
+                error: None,
+            }
+        );
+        assert!(matches!(
+            &trace.events[1],
+            ParseTraceEvent::Rejected {
+                parser: ParserKind::Bip353,
+                ..
+            }
+        ));
+        assert_eq!(
+            trace.events[2],
+            ParseTraceEvent::HttpRequest {
+                url: "https://example.com/.well-known/lnurlp/test".to_string(),
+                status: Some(404),
+                error: None,
+            }
+        );
+        assert!(matches!(
+            &trace.events[3],
+            ParseTraceEvent::Rejected {
+                parser: ParserKind::LightningAddress,
+                ..
+            }
+        ));
+
+        let (result, trace) = input_parser
+            .parse_with_trace("bitcoin:1andreas3batLhQa2FawWjeyjCqyBzypd?amount=invalid")
+            .await;
+        assert!(matches!(
+            result,
+            Err(ParseError::Bip21Error(Bip21Error::InvalidAmount))
+        ));
+        assert!(matches!(
+            &trace.events[..],
+            [ParseTraceEvent::Rejected { parser: ParserKind::Bip21, reason }]
+                if reason == "bip21 contains invalid amount"
+        ));
+
+        let (result, trace) = input_parser
+            .parse_with_trace("1andreas3batLhQa2FawWjeyjCqyBzypd")
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(
+            trace.events.last(),
+            Some(&ParseTraceEvent::Matched {
+                parser: ParserKind::Bitcoin
+            })
+        );
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_bip353_address_too_long() {
+        let mock_dns_resolver = MockDnsResolver::new();
+        let mock_rest_client = MockRestClient::new();
+        let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client);
+
+        // Local part longer than 63 chars
+        let too_long_local = "a".repeat(64) + "@example.com";
+        let result = input_parser.parse(&too_long_local).await;
+
+        // Should not be recognized as a BIP353 address
+        assert!(!matches!(
             result,
             Ok(InputType::PaymentRequest(PaymentScheme::Bip21(_)))
         ));
@@ -1112,8 +1821,8 @@ mod tests {
         println!("Debug - valid bip21 address result for '{bip21_addr}': {result:?}");
         assert!(matches!(
             result,
-            Ok(InputType::PaymentRequest(PaymentScheme::Bip21(Bip21 { amount_sat: _, asset_id: _, uri: _, extras: _, label: _, message: _, payment_methods })))
-            if payment_methods.len() == 1 && matches!(&payment_methods[0], PaymentMethod::BitcoinAddress(BitcoinAddress { address, network: _, source: _ }) if address == addr)
+            Ok(InputType::PaymentRequest(PaymentScheme::Bip21(Bip21 { amount_sat: _, asset_id: _, uri: _, extras: _, label: _, message: _, payjoin: _, payment_methods })))
+            if payment_methods.len() == 1 && matches!(&payment_methods[0], PaymentMethod::BitcoinAddress(BitcoinAddress { address, network: _, payjoin: _, source: _ }) if address == addr)
         ));
 
         // Address with amount
@@ -1122,10 +1831,10 @@ mod tests {
         println!("Debug - bip21 with amount result for '{bip21_addr_amount}': {result:?}");
         assert!(matches!(
             result,
-            Ok(InputType::PaymentRequest(PaymentScheme::Bip21(Bip21 { amount_sat, asset_id: _, uri: _, extras: _, label: _, message: _, payment_methods })))
+            Ok(InputType::PaymentRequest(PaymentScheme::Bip21(Bip21 { amount_sat, asset_id: _, uri: _, extras: _, label: _, message: _, payjoin: _, payment_methods })))
             if payment_methods.len() == 1
                 && amount_sat == Some(2000)
-                && matches!(&payment_methods[0], PaymentMethod::BitcoinAddress(BitcoinAddress { address, network: _, source: _ }) if address == addr)
+                && matches!(&payment_methods[0], PaymentMethod::BitcoinAddress(BitcoinAddress { address, network: _, payjoin: _, source: _ }) if address == addr)
         ));
 
         // Address with amount and label
@@ -1137,11 +1846,11 @@ mod tests {
         );
         assert!(matches!(
             result,
-            Ok(InputType::PaymentRequest(PaymentScheme::Bip21(Bip21 { amount_sat, asset_id: _, uri: _, extras: _, label, message: _, payment_methods })))
+            Ok(InputType::PaymentRequest(PaymentScheme::Bip21(Bip21 { amount_sat, asset_id: _, uri: _, extras: _, label, message: _, payjoin: _, payment_methods })))
             if payment_methods.len() == 1
                 && amount_sat == Some(2000)
                 && label.as_deref() == Some(lbl)
-                && matches!(&payment_methods[0], PaymentMethod::BitcoinAddress(BitcoinAddress { address, network: _, source: _ }) if address == addr)
+                && matches!(&payment_methods[0], PaymentMethod::BitcoinAddress(BitcoinAddress { address, network: _, payjoin: _, source: _ }) if address == addr)
         ));
 
         // Address with amount, label and message
@@ -1154,12 +1863,12 @@ mod tests {
         );
         assert!(matches!(
             result,
-            Ok(InputType::PaymentRequest(PaymentScheme::Bip21(Bip21 { amount_sat, asset_id: _, uri: _, extras: _, label, message, payment_methods })))
+            Ok(InputType::PaymentRequest(PaymentScheme::Bip21(Bip21 { amount_sat, asset_id: _, uri: _, extras: _, label, message, payjoin: _, payment_methods })))
             if payment_methods.len() == 1
                 && amount_sat == Some(2000)
                 && label.as_deref() == Some(lbl)
                 && message.as_deref() == Some(msg)
-                && matches!(&payment_methods[0], PaymentMethod::BitcoinAddress(BitcoinAddress { address, network: _, source: _ }) if address == addr)
+                && matches!(&payment_methods[0], PaymentMethod::BitcoinAddress(BitcoinAddress { address, network: _, payjoin: _, source: _ }) if address == addr)
         ));
     }
 
@@ -1178,10 +1887,10 @@ mod tests {
 
             assert!(matches!(
                 result,
-                Ok(InputType::PaymentRequest(PaymentScheme::Bip21(Bip21 { amount_sat, asset_id: _, uri: _, extras: _, label: _, message: _, payment_methods })))
+                Ok(InputType::PaymentRequest(PaymentScheme::Bip21(Bip21 { amount_sat, asset_id: _, uri: _, extras: _, label: _, message: _, payjoin: _, payment_methods })))
                 if payment_methods.len() == 1
                     && amount_sat == Some(amt)
-                    && matches!(&payment_methods[0], PaymentMethod::BitcoinAddress(BitcoinAddress { address, network: _, source: _ }) if address == addr)
+                    && matches!(&payment_methods[0], PaymentMethod::BitcoinAddress(BitcoinAddress { address, network: _, payjoin: _, source: _ }) if address == addr)
             ));
         }
     }
@@ -1268,7 +1977,7 @@ mod tests {
         // BOLT11 is not the first URI arg (preceded by '&')
         let result = input_parser
             .parse(&format!(
-                "bitcoin:{addr}?amount=0.00002000&lightning={bolt11}"
+                "bitcoin:{addr}?amount=0.00000011&lightning={bolt11}"
             ))
             .await;
         println!("Debug - bolt11 with fallback bitcoin address (case 2): {result:?}");
@@ -1358,6 +2067,36 @@ mod tests {
         ));
     }
 
+    #[breez_sdk_macros::async_test_all]
+    async fn test_bolt12_invoice_request() {
+        let mock_dns_resolver = MockDnsResolver::new();
+        let mock_rest_client = MockRestClient::new();
+        let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client);
+
+        let secp = Secp256k1::new();
+        let payer = Keypair::from_secret_key(&secp, &SecretKey::from_slice(&[1; 32]).unwrap());
+        let refund = RefundBuilder::new(vec![1; 32], payer.public_key(), 10_000)
+            .unwrap()
+            .description("refund".to_string())
+            .payer_note("thanks".to_string())
+            .build()
+            .unwrap()
+            .to_string();
+
+        for input in [refund.clone(), format!("lightning:{refund}")] {
+            let result = input_parser.parse(&input).await;
+            assert!(matches!(
+                result,
+                Ok(InputType::ReceiveRequest(ReceiveRequest::Bolt12InvoiceRequest(request)))
+                if request.amount_msat == Some(10_000)
+                && request.description.as_deref() == Some("refund")
+                && request.payer_note.as_deref() == Some("thanks")
+                && request.payer_signing_pubkey == payer.public_key().to_string()
+                && request.offer.is_none()
+            ));
+        }
+    }
+
     #[breez_sdk_macros::async_test_all]
     async fn test_empty_input() {
         let mock_dns_resolver = MockDnsResolver::new();
@@ -1400,9 +2139,23 @@ mod tests {
         let result = input_parser.parse(ln_address).await;
         println!("Debug - lightning address result: {result:?}");
 
-        // Since this depends on the actual implementation of lightning address resolution,
-        // we'll just check that it doesn't error out
-        assert!(result.is_ok());
+        let Ok(InputType::PaymentRequest(PaymentScheme::PaymentMethod(
+            PaymentMethod::LightningAddress(address),
+        ))) = result
+        else {
+            panic!("Expected a lightning address, got {result:?}");
+        };
+        let metadata = &address.pay_request.metadata;
+        assert_eq!(metadata.description, "WRhtV");
+        assert_eq!(metadata.long_description.as_deref(), Some("MBTrTiLCFS"));
+        assert_eq!(metadata.image.as_ref().unwrap().mime_type, "image/png");
+        let payer_data = address.pay_request.payer_data.unwrap();
+        assert!(payer_data.name.is_some_and(|name| !name.mandatory));
+        assert!(payer_data.email.is_some());
+        assert_eq!(
+            payer_data.auth.unwrap().k1,
+            "18ec6d5b96db6f219baed2f188aee7359fcf5bea11bb7d5b47157519474c2222"
+        );
     }
 
     #[breez_sdk_macros::async_test_all]
@@ -1422,6 +2175,32 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[breez_sdk_macros::async_test_all]
+    async fn test_lightning_address_resolution_error() {
+        let mock_dns_resolver = MockDnsResolver::new();
+        let mock_rest_client = MockRestClient::new();
+        mock_lnurl_pay_endpoint(&mock_rest_client, Some("user not found".to_string()));
+        mock_lnurl_withdraw_endpoint(&mock_rest_client, None);
+
+        let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client);
+
+        let result = input_parser.parse("user@domain.net").await;
+        assert!(matches!(
+            result,
+            Err(ParseError::LightningAddressResolution(LnurlError::EndpointError(reason)))
+                if reason == "user not found"
+        ));
+
+        // The endpoint of a lightning address has to be an LNURL-pay endpoint
+        let result = input_parser.parse("user@domain.net").await;
+        assert!(matches!(
+            result,
+            Err(ParseError::LightningAddressResolution(LnurlError::General(
+                _
+            )))
+        ));
+    }
+
     #[breez_sdk_macros::async_test_all]
     async fn test_lnurl() {
         let mock_dns_resolver = MockDnsResolver::new();
@@ -1490,6 +2269,45 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[breez_sdk_macros::async_test_all]
+    async fn test_lnurl_channel() {
+        let mock_dns_resolver = MockDnsResolver::new();
+        let mock_rest_client = MockRestClient::new();
+        let node_id = "03864ef025fde8fb587d989186ce6a4a186895ee44a926bfc370e2c366597a3f8f";
+        for uri in [format!("{node_id}@3.33.236.230:9735"), node_id.to_string()] {
+            mock_rest_client.add_response(MockResponse::new(
+                200,
+                json!({
+                    "uri": uri,
+                    "callback": "https://domain.com/lnurl-channel/callback",
+                    "k1": "37b4c919f871c090830cc47b92a544a30097f03430bc39670b8ec0da89f01a81",
+                    "tag": "channelRequest",
+                })
+                .to_string(),
+            ));
+        }
+
+        let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client);
+        let result = input_parser
+            .parse("lnurlc://domain.com/lnurl-channel?session=test")
+            .await;
+        let Ok(InputType::LnurlChannel(data)) = result else {
+            panic!("Expected a LNURL-channel request, got {result:?}");
+        };
+        assert_eq!(data.node_id, node_id);
+        assert_eq!(data.address, "3.33.236.230:9735");
+        assert_eq!(data.domain, "domain.com");
+
+        // The remote node has to be reachable
+        let result = input_parser
+            .parse("lnurlc://domain.com/lnurl-channel?session=test")
+            .await;
+        assert!(matches!(
+            result,
+            Err(ParseError::LnurlError(LnurlError::General(_)))
+        ));
+    }
+
     #[breez_sdk_macros::async_test_all]
     async fn test_lnurl_withdraw() {
         let mock_dns_resolver = MockDnsResolver::new();
@@ -1519,6 +2337,372 @@ mod tests {
         assert!(matches!(result, Err(ParseError::InvalidInput)));
     }
 
+    #[breez_sdk_macros::async_test_all]
+    async fn test_parse_with_network() {
+        let mock_dns_resolver = MockDnsResolver::new();
+        let mock_rest_client = MockRestClient::new();
+        let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client)
+            .with_network(BitcoinNetwork::Regtest);
+
+        let mainnet_addr = "1andreas3batLhQa2FawWjeyjCqyBzypd";
+        let result = input_parser.parse(mainnet_addr).await;
+        assert!(matches!(
+            result,
+            Err(ParseError::InvalidNetwork(BitcoinNetwork::Bitcoin))
+        ));
+        let result = input_parser
+            .parse(&format!("bitcoin:{mainnet_addr}?amount=0.001"))
+            .await;
+        assert!(matches!(
+            result,
+            Err(ParseError::InvalidNetwork(BitcoinNetwork::Bitcoin))
+        ));
+
+        let bolt11 = "lnbc110n1p38q3gtpp5ypz09jrd8p993snjwnm68cph4ftwp22le34xd4r8ftspwshxhmnsdqqxqyjw5qcqpxsp5htlg8ydpywvsa7h3u4hdn77ehs4z4e844em0apjyvmqfkzqhhd2q9qgsqqqyssqszpxzxt9uuqzymr7zxcdccj5g69s8q7zzjs7sgxn9ejhnvdh6gqjcy22mss2yexunagm5r2gqczh8k24cwrqml3njskm548aruhpwssq9nvrvz";
+        let result = input_parser.parse(bolt11).await;
+        assert!(matches!(
+            result,
+            Err(ParseError::InvalidNetwork(BitcoinNetwork::Bitcoin))
+        ));
+
+        // Regtest shares the legacy address encoding of the test networks
+        let result = input_parser
+            .parse("mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn")
+            .await;
+        assert!(result.is_ok(), "{result:?}");
+
+        // The test networks share their segwit address encoding
+        let testnet_addr = "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx";
+        let mock_dns_resolver = MockDnsResolver::new();
+        let mock_rest_client = MockRestClient::new();
+        let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client)
+            .with_network(BitcoinNetwork::Testnet4);
+        let result = input_parser.parse(testnet_addr).await;
+        assert!(result.is_ok(), "{result:?}");
+        let result = input_parser.parse(mainnet_addr).await;
+        assert!(matches!(
+            result,
+            Err(ParseError::InvalidNetwork(BitcoinNetwork::Bitcoin))
+        ));
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_invalid_checksum() {
+        let mock_dns_resolver = MockDnsResolver::new();
+        let mock_rest_client = MockRestClient::new();
+        let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client);
+
+        // Segwit address with its last character changed
+        let result = input_parser
+            .parse("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5")
+            .await;
+        assert!(matches!(result, Err(ParseError::InvalidChecksum)));
+
+        // Invoice with its last character changed
+        let bolt11 = "lnbc110n1p38q3gtpp5ypz09jrd8p993snjwnm68cph4ftwp22le34xd4r8ftspwshxhmnsdqqxqyjw5qcqpxsp5htlg8ydpywvsa7h3u4hdn77ehs4z4e844em0apjyvmqfkzqhhd2q9qgsqqqyssqszpxzxt9uuqzymr7zxcdccj5g69s8q7zzjs7sgxn9ejhnvdh6gqjcy22mss2yexunagm5r2gqczh8k24cwrqml3njskm548aruhpwssq9nvrvq";
+        let result = input_parser.parse(bolt11).await;
+        assert!(matches!(result, Err(ParseError::InvalidChecksum)));
+        let result = input_parser.parse(&format!("lightning:{bolt11}")).await;
+        assert!(matches!(result, Err(ParseError::InvalidChecksum)));
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_unsupported_hrp() {
+        let mock_dns_resolver = MockDnsResolver::new();
+        let mock_rest_client = MockRestClient::new();
+        let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client);
+
+        let input = encode_silent_payment_address("ltc", 1, &[0; 32]);
+        let result = input_parser.parse(&input).await;
+        assert!(matches!(result, Err(ParseError::UnsupportedHrp(hrp)) if hrp == "ltc"));
+    }
+
+    /// Receiving test vector from BIP-352
+    const SILENT_PAYMENT_ADDRESS: &str = "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc9pkqwv";
+    const SILENT_PAYMENT_SCAN_PUBKEY: &str =
+        "0220bcfac5b99e04ad1a06ddfb016ee13582609d60b6291e98d01a9bc9a16c96d4";
+    const SILENT_PAYMENT_SPEND_PUBKEY: &str =
+        "025cc9856d6f8375350e123978daac200c260cb5b5ae83106cab90484dcd8fcf36";
+
+    fn encode_silent_payment_address(hrp: &str, version: u8, payload: &[u8]) -> String {
+        payload
+            .iter()
+            .copied()
+            .bytes_to_fes()
+            .with_checksum::<Bech32m>(&Hrp::parse(hrp).unwrap())
+            .with_witness_version(Fe32::try_from(version).unwrap())
+            .chars()
+            .collect()
+    }
+
+    fn silent_payment_keys() -> Vec<u8> {
+        let mut keys = hex::decode(SILENT_PAYMENT_SCAN_PUBKEY).unwrap();
+        keys.extend(hex::decode(SILENT_PAYMENT_SPEND_PUBKEY).unwrap());
+        keys
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_silent_payment_address() {
+        let mock_dns_resolver = MockDnsResolver::new();
+        let mock_rest_client = MockRestClient::new();
+        let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client);
+
+        for input in [
+            SILENT_PAYMENT_ADDRESS.to_string(),
+            SILENT_PAYMENT_ADDRESS.to_uppercase(),
+        ] {
+            let result = input_parser.parse(&input).await;
+            assert!(matches!(
+                result,
+                Ok(InputType::PaymentRequest(PaymentScheme::PaymentMethod(
+                    PaymentMethod::SilentPaymentAddress(sp)
+                )))
+                if sp.address == input
+                && sp.network == BitcoinNetwork::Bitcoin
+                && sp.scan_pubkey == SILENT_PAYMENT_SCAN_PUBKEY
+                && sp.spend_pubkey == SILENT_PAYMENT_SPEND_PUBKEY
+                && sp.version == 0
+            ));
+        }
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_silent_payment_address_network() {
+        let mock_dns_resolver = MockDnsResolver::new();
+        let mock_rest_client = MockRestClient::new();
+        let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client);
+
+        for (hrp, expected_network, valid_on_regtest) in [
+            ("sp", BitcoinNetwork::Bitcoin, false),
+            ("tsp", BitcoinNetwork::Testnet3, true),
+            ("sprt", BitcoinNetwork::Regtest, true),
+        ] {
+            let address = encode_silent_payment_address(hrp, 0, &silent_payment_keys());
+            let result = input_parser.parse(&address).await;
+            assert!(matches!(
+                result,
+                Ok(InputType::PaymentRequest(PaymentScheme::PaymentMethod(
+                    PaymentMethod::SilentPaymentAddress(sp)
+                )))
+                if sp.network == expected_network
+                && sp.is_valid_for_network(expected_network)
+                && sp.is_valid_for_network(BitcoinNetwork::Regtest) == valid_on_regtest
+            ));
+        }
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_silent_payment_address_version() {
+        let mock_dns_resolver = MockDnsResolver::new();
+        let mock_rest_client = MockRestClient::new();
+        let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client);
+        let mut extended_keys = silent_payment_keys();
+        extended_keys.extend([0; 10]);
+
+        // Future versions may append data, which is ignored.
+        let address = encode_silent_payment_address("sp", 1, &extended_keys);
+        let result = input_parser.parse(&address).await;
+        assert!(matches!(
+            result,
+            Ok(InputType::PaymentRequest(PaymentScheme::PaymentMethod(
+                PaymentMethod::SilentPaymentAddress(sp)
+            )))
+            if sp.version == 1
+            && sp.scan_pubkey == SILENT_PAYMENT_SCAN_PUBKEY
+            && sp.spend_pubkey == SILENT_PAYMENT_SPEND_PUBKEY
+        ));
+
+        for address in [
+            // Version 0 must contain exactly the two keys.
+            encode_silent_payment_address("sp", 0, &extended_keys),
+            encode_silent_payment_address("sp", 0, &silent_payment_keys()[..65]),
+            // Version 31 is reserved for backwards incompatible changes.
+            encode_silent_payment_address("sp", 31, &silent_payment_keys()),
+            // Invalid public keys.
+            encode_silent_payment_address("sp", 0, &[0; 66]),
+        ] {
+            let result = input_parser.parse(&address).await;
+            assert!(
+                matches!(result, Err(ParseError::InvalidInput)),
+                "{address}: {result:?}"
+            );
+        }
+
+        // Unknown prefix.
+        let address = encode_silent_payment_address("xsp", 0, &silent_payment_keys());
+        let result = input_parser.parse(&address).await;
+        assert!(matches!(result, Err(ParseError::UnsupportedHrp(hrp)) if hrp == "xsp"));
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_silent_payment_address_bip21() {
+        let mock_dns_resolver = MockDnsResolver::new();
+        let mock_rest_client = MockRestClient::new();
+        let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client);
+
+        let addr = "1andreas3batLhQa2FawWjeyjCqyBzypd";
+        for bip_21 in [
+            format!("bitcoin:{addr}?sp={SILENT_PAYMENT_ADDRESS}"),
+            format!("bitcoin:?sp={SILENT_PAYMENT_ADDRESS}"),
+        ] {
+            let result = input_parser.parse(&bip_21).await;
+            assert!(matches!(
+                result,
+                Ok(InputType::PaymentRequest(PaymentScheme::Bip21(bip21)))
+                if bip21.payment_methods.iter().any(|pm| matches!(
+                    pm,
+                    PaymentMethod::SilentPaymentAddress(sp) if sp.address == SILENT_PAYMENT_ADDRESS
+                ))
+            ));
+        }
+    }
+
+    const LIQUID_ADDRESS: &str =
+        "VJLCbLBTCdxhWyjVLdjcSmGAksVMtabYg15maSi93zknQD2ihC38R7CUd8KbDFnV8A4hiykxnRB3Uv6d";
+    const LIQUID_BTC_ASSET_ID: &str =
+        "6f0279e9ed041c3d710a9f57d0c02928416460c4b722ae3457a11eec381c526d";
+    const LIQUID_USDT_ASSET_ID: &str =
+        "ce091c998b83c78bb71a632313ba3760f1763d9cfcffae02258ffa9865a37bd2";
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_liquid_address() {
+        let mock_dns_resolver = MockDnsResolver::new();
+        let mock_rest_client = MockRestClient::new();
+        let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client);
+
+        for (address, expected_network) in [
+            (LIQUID_ADDRESS, BitcoinNetwork::Bitcoin),
+            (
+                "Q7qcjTLsYGoMA7TjUp97R6E6AM5VKqBik6",
+                BitcoinNetwork::Bitcoin,
+            ),
+            (
+                "2dxmEBXc2qMYcLSKiDBxdEePY3Ytixmnh4E",
+                BitcoinNetwork::Regtest,
+            ),
+        ] {
+            let result = input_parser.parse(address).await;
+            assert!(matches!(
+                result,
+                Ok(InputType::PaymentRequest(PaymentScheme::PaymentMethod(
+                    PaymentMethod::LiquidAddress(liquid_address)
+                )))
+                if liquid_address.address == address && liquid_address.network == expected_network
+            ));
+        }
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_liquid_bip21() {
+        let mock_dns_resolver = MockDnsResolver::new();
+        let mock_rest_client = MockRestClient::new();
+        let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client);
+
+        let uri = format!(
+            "liquidnetwork:{LIQUID_ADDRESS}?amount=0.00010000&assetid={LIQUID_BTC_ASSET_ID}&label=Merchant"
+        );
+        let result = input_parser.parse(&uri).await;
+        assert!(matches!(
+            result,
+            Ok(InputType::PaymentRequest(PaymentScheme::Bip21(bip21)))
+            if bip21.amount_sat == Some(10_000)
+            && bip21.asset_id.as_deref() == Some(LIQUID_BTC_ASSET_ID)
+            && bip21.label.as_deref() == Some("Merchant")
+            && matches!(
+                &bip21.payment_methods[..],
+                [PaymentMethod::LiquidAddress(liquid_address)]
+                if liquid_address.address == LIQUID_ADDRESS
+                && liquid_address.asset_id.as_deref() == Some(LIQUID_BTC_ASSET_ID)
+                && liquid_address.is_lbtc()
+                && liquid_address.network == BitcoinNetwork::Bitcoin
+                && liquid_address.source.bip_21_uri.as_deref() == Some(uri.as_str())
+            )
+        ));
+
+        // Amounts of other assets are not in sats.
+        let uri =
+            format!("liquidnetwork:{LIQUID_ADDRESS}?amount=0.5&assetid={LIQUID_USDT_ASSET_ID}");
+        let result = input_parser.parse(&uri).await;
+        assert!(matches!(
+            result,
+            Ok(InputType::PaymentRequest(PaymentScheme::Bip21(bip21)))
+            if bip21.amount_sat.is_none()
+            && bip21.asset_amount.as_deref() == Some("0.5")
+            && bip21.asset_id.as_deref() == Some(LIQUID_USDT_ASSET_ID)
+            && matches!(
+                &bip21.payment_methods[..],
+                [PaymentMethod::LiquidAddress(liquid_address)]
+                if liquid_address.asset_id.as_deref() == Some(LIQUID_USDT_ASSET_ID)
+                && !liquid_address.is_lbtc()
+            )
+        ));
+
+        // Without amount the asset id is optional.
+        let result = input_parser
+            .parse(&format!("liquidnetwork:{LIQUID_ADDRESS}"))
+            .await;
+        assert!(matches!(
+            result,
+            Ok(InputType::PaymentRequest(PaymentScheme::Bip21(bip21)))
+            if bip21.amount_sat.is_none() && bip21.asset_id.is_none()
+        ));
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_liquid_bip21_invalid() {
+        let mock_dns_resolver = MockDnsResolver::new();
+        let mock_rest_client = MockRestClient::new();
+        let input_parser = InputParser::new(mock_dns_resolver, mock_rest_client);
+
+        let result = input_parser
+            .parse(&format!("liquidnetwork:{LIQUID_ADDRESS}?amount=0.0001"))
+            .await;
+        assert!(matches!(
+            result,
+            Err(ParseError::Bip21Error(Bip21Error::MissingAssetId))
+        ));
+
+        let result = input_parser
+            .parse(&format!(
+                "liquidnetwork:{LIQUID_ADDRESS}?amount=0.000000001&assetid={LIQUID_BTC_ASSET_ID}"
+            ))
+            .await;
+        assert!(matches!(
+            result,
+            Err(ParseError::Bip21Error(Bip21Error::InvalidAmount))
+        ));
+
+        let result = input_parser
+            .parse(&format!(
+                "liquidnetwork:{LIQUID_ADDRESS}?amount=0,5&assetid={LIQUID_USDT_ASSET_ID}"
+            ))
+            .await;
+        assert!(matches!(
+            result,
+            Err(ParseError::Bip21Error(Bip21Error::InvalidAmount))
+        ));
+
+        let result = input_parser
+            .parse(&format!("liquidnetwork:{LIQUID_ADDRESS}?assetid=6f0279e9"))
+            .await;
+        assert!(matches!(
+            result,
+            Err(ParseError::Bip21Error(Bip21Error::InvalidParameter(param))) if param == "assetid"
+        ));
+
+        // The address must be for the network of the scheme.
+        for uri in [
+            format!("liquidtestnet:{LIQUID_ADDRESS}"),
+            "liquidnetwork:1andreas3batLhQa2FawWjeyjCqyBzypd".to_string(),
+        ] {
+            let result = input_parser.parse(&uri).await;
+            assert!(matches!(
+                result,
+                Err(ParseError::Bip21Error(Bip21Error::InvalidAddress))
+            ));
+        }
+    }
+
     #[breez_sdk_macros::async_test_all]
     async fn test_trim_input() {
         let mock_dns_resolver = MockDnsResolver::new();
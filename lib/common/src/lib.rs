@@ -1,3 +1,4 @@
+pub mod bolt12;
 pub mod breez_server;
 pub mod buy;
 pub mod dns;
@@ -8,6 +9,7 @@ pub mod input;
 pub mod lnurl;
 pub mod network;
 pub mod rest;
+pub mod silent_payment;
 pub mod tonic_wrap;
 pub mod utils;
 
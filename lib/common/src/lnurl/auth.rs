@@ -65,32 +65,48 @@ pub async fn perform_lnurl_auth<C: RestClient + ?Sized, S: LnurlAuthSigner>(
         warn!("Lnurl auth URL is invalid: {:?}", e);
         LnurlError::InvalidUri
     })?;
+    let (key, sig) = sign_k1(signer, url, &req_data.k1).await?;
+
+    // <LNURL_hostname_and_path>?<LNURL_existing_query_parameters>&sig=<hex(sign(utf8ToBytes(k1), linkingPrivKey))>&key=<hex(linkingKey)>
+    let mut callback_url = Url::from_str(&req_data.url).map_err(|e| {
+        warn!("Lnurl auth callback URL is invalid: {:?}", e);
+        LnurlError::InvalidUri
+    })?;
+    callback_url.query_pairs_mut().append_pair("sig", &sig);
+    callback_url.query_pairs_mut().append_pair("key", &key);
+    let (response, _) = rest_client.get(callback_url.as_ref()).await?;
+    Ok(parse_json(&response)?)
+}
+
+/// Signs the challenge with the linking key of the domain of the url, returning the hex encoded
+/// linking key and signature.
+pub(crate) async fn sign_k1<S: LnurlAuthSigner>(
+    signer: &S,
+    url: Url,
+    k1: &str,
+) -> LnurlResult<(String, String)> {
     let derivation_path = get_derivation_path(signer, url).await?;
     let sig = signer
         .sign_ecdsa(
-            &hex::decode(&req_data.k1).map_err(|_| LnurlError::InvalidK1)?,
+            &hex::decode(k1).map_err(|_| LnurlError::InvalidK1)?,
             &derivation_path,
         )
         .await?;
-    let xpub_bytes = signer.derive_bip32_pub_key(&derivation_path).await?;
+    let key = linking_key(signer, &derivation_path).await?;
+    Ok((key, hex::encode(&sig)))
+}
+
+/// The hex encoded linking key at the derivation path, see LUD-05
+pub(crate) async fn linking_key<S: LnurlAuthSigner>(
+    signer: &S,
+    derivation_path: &[ChildNumber],
+) -> LnurlResult<String> {
+    let xpub_bytes = signer.derive_bip32_pub_key(derivation_path).await?;
     let xpub = Xpub::decode(xpub_bytes.as_slice()).map_err(|e| {
         error!("Failed to decode xpub: {:?}", e);
         LnurlError::General("failed to decode xpub".to_string())
     })?;
-
-    // <LNURL_hostname_and_path>?<LNURL_existing_query_parameters>&sig=<hex(sign(utf8ToBytes(k1), linkingPrivKey))>&key=<hex(linkingKey)>
-    let mut callback_url = Url::from_str(&req_data.url).map_err(|e| {
-        warn!("Lnurl auth callback URL is invalid: {:?}", e);
-        LnurlError::InvalidUri
-    })?;
-    callback_url
-        .query_pairs_mut()
-        .append_pair("sig", &hex::encode(&sig));
-    callback_url
-        .query_pairs_mut()
-        .append_pair("key", &xpub.public_key.to_string());
-    let (response, _) = rest_client.get(callback_url.as_ref()).await?;
-    Ok(parse_json(&response)?)
+    Ok(xpub.public_key.to_string())
 }
 
 pub fn validate_request(url: &reqwest::Url) -> Result<LnurlAuthRequestData, LnurlError> {
@@ -0,0 +1,250 @@
+use std::str::FromStr;
+
+use bitcoin::secp256k1::PublicKey;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::rest::{RestClient, parse_json};
+
+use super::{
+    LnurlCallbackStatus,
+    error::{LnurlError, LnurlResult},
+};
+
+/// Wrapped in a [`crate::input::InputType::LnurlChannel`], this is the result of [`parse`] when
+/// given a LNURL-channel endpoint.
+///
+/// It represents the endpoint's parameters for the LNURL workflow.
+///
+/// See <https://github.com/lnurl/luds/blob/luds/02.md>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct LnurlChannelRequestData {
+    /// The URI of the remote node, as `node_id@host:port`
+    pub uri: String,
+    pub callback: String,
+    /// Hex encoded 32 bytes of challenge
+    pub k1: String,
+
+    /// The hex encoded public key of the remote node, parsed from `uri`
+    #[serde(skip_serializing, skip_deserializing)]
+    pub node_id: String,
+
+    /// The `host:port` address of the remote node, parsed from `uri`
+    #[serde(skip_serializing, skip_deserializing)]
+    pub address: String,
+
+    /// Indicates the domain of the LNURL-channel service, to be shown to the user when asking
+    /// whether to accept the channel.
+    #[serde(skip_serializing, skip_deserializing)]
+    pub domain: String,
+}
+
+/// The answer to a LNURL-channel request
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum LnurlChannelAction {
+    /// Asks the service to open the channel, to be announced unless `private`
+    Accept { private: bool },
+    /// Tells the service the channel won't be accepted
+    Decline,
+}
+
+/// Performs the last step of LNURL-channel, calling the callback with the local node id as
+/// `remoteid`. The local node is expected to connect to the remote node before accepting.
+/// Declining doesn't require the local node id, the service matches the request by `k1`.
+pub async fn perform_lnurl_channel<C: RestClient + ?Sized>(
+    rest_client: &C,
+    req_data: &LnurlChannelRequestData,
+    local_node_id: Option<&str>,
+    action: &LnurlChannelAction,
+) -> LnurlResult<LnurlCallbackStatus> {
+    let callback_url = callback_url(req_data, local_node_id, action)?;
+    let (response, _) = rest_client.get(callback_url.as_ref()).await?;
+    Ok(parse_json(&response)?)
+}
+
+/// Validates the request returned by the service, filling in the remote node info and the
+/// domain.
+pub fn validate_request(
+    data: LnurlChannelRequestData,
+    domain: String,
+) -> LnurlResult<LnurlChannelRequestData> {
+    let (node_id, address) = data
+        .uri
+        .split_once('@')
+        .ok_or_else(|| LnurlError::General("node uri is missing the node id".to_string()))?;
+    PublicKey::from_str(node_id)
+        .map_err(|_| LnurlError::General("node uri has an invalid node id".to_string()))?;
+    let port = address
+        .rsplit_once(':')
+        .and_then(|(host, port)| (!host.is_empty()).then_some(port));
+    if port.and_then(|port| port.parse::<u16>().ok()).is_none() {
+        return Err(LnurlError::General(
+            "node uri has an invalid address".to_string(),
+        ));
+    }
+
+    let k1_bytes = hex::decode(&data.k1).map_err(|_| LnurlError::InvalidK1)?;
+    if k1_bytes.len() != 32 {
+        return Err(LnurlError::InvalidK1);
+    }
+
+    Ok(LnurlChannelRequestData {
+        node_id: node_id.to_string(),
+        address: address.to_string(),
+        domain,
+        ..data
+    })
+}
+
+fn callback_url(
+    req_data: &LnurlChannelRequestData,
+    local_node_id: Option<&str>,
+    action: &LnurlChannelAction,
+) -> LnurlResult<Url> {
+    // <callback>?k1=<k1>&remoteid=<local node id>&private=<1/0>, or &cancel=1 when declining,
+    // where the local node id is optional
+    let mut callback_url = Url::from_str(&req_data.callback).map_err(|e| {
+        warn!("Lnurl channel callback URL is invalid: {:?}", e);
+        LnurlError::InvalidUri
+    })?;
+    callback_url
+        .query_pairs_mut()
+        .append_pair("k1", &req_data.k1);
+    if let Some(local_node_id) = local_node_id {
+        callback_url
+            .query_pairs_mut()
+            .append_pair("remoteid", local_node_id);
+    }
+    match action {
+        LnurlChannelAction::Accept { private } => {
+            if local_node_id.is_none() {
+                return Err(LnurlError::General(
+                    "accepting a channel requires the local node id".to_string(),
+                ));
+            }
+            callback_url
+                .query_pairs_mut()
+                .append_pair("private", if *private { "1" } else { "0" });
+        }
+        LnurlChannelAction::Decline => {
+            callback_url.query_pairs_mut().append_pair("cancel", "1");
+        }
+    }
+    Ok(callback_url)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        lnurl::{LnurlCallbackStatus, error::LnurlError},
+        test_utils::mock_rest_client::{MockResponse, MockRestClient},
+    };
+
+    use super::{
+        LnurlChannelAction, LnurlChannelRequestData, callback_url, perform_lnurl_channel,
+        validate_request,
+    };
+
+    const NODE_ID: &str = "03864ef025fde8fb587d989186ce6a4a186895ee44a926bfc370e2c366597a3f8f";
+    const K1: &str = "37b4c919f871c090830cc47b92a544a30097f03430bc39670b8ec0da89f01a81";
+
+    fn request_data(uri: &str) -> LnurlChannelRequestData {
+        serde_json::from_value(json!({
+            "uri": uri,
+            "callback": "https://example.com/channel?session=1",
+            "k1": K1,
+            "tag": "channelRequest",
+        }))
+        .unwrap()
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_validate_request() {
+        let data = validate_request(
+            request_data(&format!("{NODE_ID}@3.33.236.230:9735")),
+            "example.com".to_string(),
+        )
+        .unwrap();
+        assert_eq!(data.node_id, NODE_ID);
+        assert_eq!(data.address, "3.33.236.230:9735");
+        assert_eq!(data.domain, "example.com");
+
+        for uri in [
+            "3.33.236.230:9735".to_string(),
+            "invalid@3.33.236.230:9735".to_string(),
+            format!("{NODE_ID}@3.33.236.230"),
+            format!("{NODE_ID}@:9735"),
+        ] {
+            assert!(
+                validate_request(request_data(&uri), "example.com".to_string()).is_err(),
+                "{uri} should be invalid"
+            );
+        }
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_callback_url() {
+        let data = request_data(&format!("{NODE_ID}@3.33.236.230:9735"));
+        let url = callback_url(
+            &data,
+            Some(NODE_ID),
+            &LnurlChannelAction::Accept { private: true },
+        )
+        .unwrap();
+        assert_eq!(
+            url.as_str(),
+            format!("https://example.com/channel?session=1&k1={K1}&remoteid={NODE_ID}&private=1")
+        );
+
+        let url = callback_url(&data, Some(NODE_ID), &LnurlChannelAction::Decline).unwrap();
+        assert_eq!(
+            url.as_str(),
+            format!("https://example.com/channel?session=1&k1={K1}&remoteid={NODE_ID}&cancel=1")
+        );
+
+        let url = callback_url(&data, None, &LnurlChannelAction::Decline).unwrap();
+        assert_eq!(
+            url.as_str(),
+            format!("https://example.com/channel?session=1&k1={K1}&cancel=1")
+        );
+        assert!(matches!(
+            callback_url(&data, None, &LnurlChannelAction::Accept { private: false }),
+            Err(LnurlError::General(_))
+        ));
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_perform_lnurl_channel() {
+        let rest_client = MockRestClient::new();
+        rest_client
+            .add_response(MockResponse::new(
+                200,
+                json!({ "status": "OK" }).to_string(),
+            ))
+            .add_response(MockResponse::new(
+                200,
+                json!({ "status": "ERROR", "reason": "expired" }).to_string(),
+            ));
+        let data = request_data(&format!("{NODE_ID}@3.33.236.230:9735"));
+
+        let status = perform_lnurl_channel(&rest_client, &data, None, &LnurlChannelAction::Decline)
+            .await
+            .unwrap();
+        assert!(matches!(status, LnurlCallbackStatus::Ok));
+        let status = perform_lnurl_channel(&rest_client, &data, None, &LnurlChannelAction::Decline)
+            .await
+            .unwrap();
+        assert!(
+            matches!(status, LnurlCallbackStatus::ErrorStatus { data } if data.reason == "expired")
+        );
+        assert!(matches!(
+            perform_lnurl_channel(&rest_client, &data, None, &LnurlChannelAction::Decline).await,
+            Err(LnurlError::ServiceConnectivity(_))
+        ));
+    }
+}
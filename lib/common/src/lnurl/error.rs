@@ -31,6 +31,25 @@ pub enum LnurlError {
     UnknownScheme,
     #[error("lnurl has unknown scheme")]
     InvalidUri,
+    #[error("invalid amount: {0}")]
+    InvalidAmount(String),
+    #[error("invalid invoice: {0}")]
+    InvalidInvoice(String),
+    #[error("lnurl endpoint does not support nostr zaps")]
+    ZapsNotSupported,
+    #[error("lnurl endpoint requires payer data: {0}")]
+    MissingPayerData(String),
+    #[error("invalid lnurl-pay metadata: {0}")]
+    InvalidMetadata(String),
+}
+
+pub type LightningAddressResult<T, E = LightningAddressError> = Result<T, E>;
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
+pub enum LightningAddressError {
+    #[error("invalid username: {0}")]
+    InvalidUsername(String),
 }
 
 impl From<TryFromSliceError> for LnurlError {
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::error::{LightningAddressError, LightningAddressResult};
+
+/// Claims a username on a Lightning address server. The signature of [`register_message`] by
+/// the wallet's node key proves ownership of the registration.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RegisterLnurlPayRequest {
+    pub username: String,
+    pub webhook_url: String,
+    pub time: u64,
+    pub signature: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RegisterLnurlPayResponse {
+    /// The claimed `username@domain` address
+    pub lightning_address: String,
+    /// The bech32 encoded LNURL-pay endpoint of the address
+    pub lnurl: String,
+}
+
+/// Releases the username registered with the webhook, signed like [`unregister_message`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UnregisterLnurlPayRequest {
+    pub webhook_url: String,
+    pub time: u64,
+    pub signature: String,
+}
+
+/// Posted by the server to the webhook of the wallet when a payer requests an invoice from the
+/// `/.well-known/lnurlp/<username>` endpoint, see LUD-06 and LUD-16
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LnurlPayInvoiceRequest {
+    pub amount_msat: u64,
+    /// The metadata of the LNURL-pay endpoint, which the description hash of the invoice must
+    /// commit to
+    pub metadata: String,
+}
+
+/// The answer of the webhook, relayed to the payer by the server.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LnurlPayInvoiceResponse {
+    /// The BOLT11 invoice
+    pub pr: String,
+}
+
+/// The message signed to register a username.
+pub fn register_message(time: u64, username: &str, webhook_url: &str) -> String {
+    format!("{time}-{username}-{webhook_url}")
+}
+
+/// The message signed to release a username.
+pub fn unregister_message(time: u64, webhook_url: &str) -> String {
+    format!("{time}-{webhook_url}")
+}
+
+/// The LUD-06 metadata of the LNURL-pay endpoint of a Lightning address, identifying it as
+/// required by LUD-16.
+pub fn lnurl_pay_metadata(lightning_address: &str) -> String {
+    json!([
+        ["text/plain", format!("Pay to {lightning_address}")],
+        ["text/identifier", lightning_address],
+    ])
+    .to_string()
+}
+
+/// Checks the username only contains the characters allowed by LUD-16.
+pub fn validate_username(username: &str) -> LightningAddressResult<()> {
+    let is_valid = !username.is_empty()
+        && username.len() <= 64
+        && username
+            .chars()
+            .all(|c| matches!(c, 'a'..='z' | '0'..='9' | '-' | '_' | '.'));
+    if !is_valid {
+        return Err(LightningAddressError::InvalidUsername(username.to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lnurl::error::LightningAddressError;
+
+    use super::{lnurl_pay_metadata, validate_username};
+
+    #[breez_sdk_macros::test_all]
+    fn test_validate_username() {
+        assert!(validate_username("alice").is_ok());
+        assert!(validate_username("alice.bob-99_").is_ok());
+        assert!(matches!(
+            validate_username(""),
+            Err(LightningAddressError::InvalidUsername(_))
+        ));
+        assert!(matches!(
+            validate_username("Alice"),
+            Err(LightningAddressError::InvalidUsername(_))
+        ));
+        assert!(matches!(
+            validate_username("alice@example.com"),
+            Err(LightningAddressError::InvalidUsername(_))
+        ));
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_lnurl_pay_metadata() {
+        assert_eq!(
+            lnurl_pay_metadata("alice@example.com"),
+            r#"[["text/plain","Pay to alice@example.com"],["text/identifier","alice@example.com"]]"#
+        );
+    }
+}
@@ -1,5 +1,9 @@
 pub mod auth;
+pub mod channel;
 pub mod error;
+pub mod lightning_address;
+pub mod pay;
+pub mod zap;
 
 use serde::{Deserialize, Serialize};
 
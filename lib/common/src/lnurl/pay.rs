@@ -0,0 +1,781 @@
+use std::str::FromStr;
+
+use bitcoin::{
+    base64::{Engine, engine::general_purpose::STANDARD},
+    hashes::{Hash, sha256},
+};
+use lightning::bolt11_invoice::{Bolt11Invoice, Bolt11InvoiceDescriptionRef};
+use percent_encoding_rfc3986::{NON_ALPHANUMERIC, utf8_percent_encode};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::debug;
+use web_time::{Duration, Instant};
+
+use crate::{
+    ensure_sdk,
+    input::{LnurlPayImage, LnurlPayMetadata, LnurlPayRequest},
+    rest::RestClient,
+};
+
+use super::{
+    LnurlCallbackStatus,
+    auth::{LnurlAuthSigner, get_derivation_path, linking_key, sign_k1},
+    error::{LnurlError, LnurlResult},
+};
+
+/// How often [`poll_payment_verification`] checks the verify URL
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+const VERIFY_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The invoice returned by a LNURL-pay callback, see LUD-06
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LnurlPayInvoice {
+    pub pr: String,
+    /// The URL to check whether the invoice was settled, see LUD-21
+    #[serde(default)]
+    pub verify: Option<String>,
+}
+
+/// The payer identity shared with LNURL-pay endpoints asking for it, see LUD-18
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct LnurlPayerDetails {
+    pub name: Option<String>,
+    pub email: Option<String>,
+    /// An internet identifier of the payer, such as their Lightning address
+    pub identifier: Option<String>,
+}
+
+/// The payer data passed to the LNURL-pay callback, see LUD-18
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct LnurlPayerData {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pubkey: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<LnurlPayerDataAuth>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LnurlPayerDataAuth {
+    /// The hex encoded linking key
+    pub key: String,
+    pub k1: String,
+    /// The hex encoded signature of `k1` by the linking key
+    pub sig: String,
+}
+
+/// The settlement status of a LNURL-pay invoice, see LUD-21
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct LnurlPaymentVerification {
+    pub settled: bool,
+    /// The hex encoded preimage, once the invoice is settled
+    #[serde(default)]
+    pub preimage: Option<String>,
+    pub pr: String,
+}
+
+/// Parses the LNURL-pay metadata, a JSON array of `[type, content]` items with exactly one
+/// `text/plain` description, as per LUD-06.
+pub fn parse_metadata(metadata_str: &str) -> LnurlResult<LnurlPayMetadata> {
+    let items: Vec<Vec<Value>> = serde_json::from_str(metadata_str)
+        .map_err(|e| LnurlError::InvalidMetadata(e.to_string()))?;
+
+    let mut description = None;
+    let mut metadata = LnurlPayMetadata::default();
+    for item in items {
+        let [Value::String(item_type), content, ..] = item.as_slice() else {
+            return Err(LnurlError::InvalidMetadata(
+                "items must be [type, content] pairs".to_string(),
+            ));
+        };
+        let field = match item_type.as_str() {
+            "text/plain" => &mut description,
+            "text/long-desc" => &mut metadata.long_description,
+            "text/identifier" => &mut metadata.identifier,
+            "text/email" => &mut metadata.email,
+            "image/png;base64" | "image/jpeg;base64" => {
+                ensure_sdk!(
+                    metadata.image.is_none(),
+                    LnurlError::InvalidMetadata("multiple images".to_string())
+                );
+                let data = metadata_content(item_type, content)?;
+                STANDARD.decode(&data).map_err(|e| {
+                    LnurlError::InvalidMetadata(format!("invalid {item_type} image: {e}"))
+                })?;
+                metadata.image = Some(LnurlPayImage {
+                    mime_type: item_type.trim_end_matches(";base64").to_string(),
+                    data,
+                });
+                continue;
+            }
+            _ => continue,
+        };
+        ensure_sdk!(
+            field.is_none(),
+            LnurlError::InvalidMetadata(format!("multiple {item_type} items"))
+        );
+        *field = Some(metadata_content(item_type, content)?);
+    }
+
+    metadata.description = description
+        .ok_or_else(|| LnurlError::InvalidMetadata("missing text/plain item".to_string()))?;
+    Ok(metadata)
+}
+
+fn metadata_content(item_type: &str, content: &Value) -> LnurlResult<String> {
+    content
+        .as_str()
+        .map(ToString::to_string)
+        .ok_or_else(|| LnurlError::InvalidMetadata(format!("{item_type} content is not a string")))
+}
+
+/// Builds the payer data the endpoint asks for, if any. Only the requested fields are shared.
+/// The `pubkey` and `auth` fields use the LNURL-auth linking key of the endpoint's domain, as
+/// per LUD-05.
+pub async fn build_payer_data<S: LnurlAuthSigner>(
+    signer: &S,
+    pay_request: &LnurlPayRequest,
+    details: &LnurlPayerDetails,
+) -> LnurlResult<Option<LnurlPayerData>> {
+    let Some(requested) = &pay_request.payer_data else {
+        return Ok(None);
+    };
+    let url = Url::from_str(&pay_request.url).map_err(|_| LnurlError::InvalidUri)?;
+
+    let mut payer_data = LnurlPayerData::default();
+    if let Some(auth) = &requested.auth {
+        let (key, sig) = sign_k1(signer, url.clone(), &auth.k1).await?;
+        payer_data.auth = Some(LnurlPayerDataAuth {
+            key,
+            k1: auth.k1.clone(),
+            sig,
+        });
+    }
+    if requested.pubkey.is_some() {
+        payer_data.pubkey = Some(match &payer_data.auth {
+            Some(auth) => auth.key.clone(),
+            None => linking_key(signer, &get_derivation_path(signer, url).await?).await?,
+        });
+    }
+    for (field, requirement, value, detail) in [
+        ("name", &requested.name, &mut payer_data.name, &details.name),
+        (
+            "email",
+            &requested.email,
+            &mut payer_data.email,
+            &details.email,
+        ),
+        (
+            "identifier",
+            &requested.identifier,
+            &mut payer_data.identifier,
+            &details.identifier,
+        ),
+    ] {
+        let Some(requirement) = requirement else {
+            continue;
+        };
+        ensure_sdk!(
+            detail.is_some() || !requirement.mandatory,
+            LnurlError::MissingPayerData(field.to_string())
+        );
+        value.clone_from(detail);
+    }
+    Ok(Some(payer_data))
+}
+
+/// Requests an invoice from the LNURL-pay callback. The invoice is checked to be for the amount
+/// and to commit to the metadata of the endpoint and the payer data, or to the zap request when
+/// given.
+pub async fn request_invoice<C: RestClient + ?Sized>(
+    rest_client: &C,
+    pay_request: &LnurlPayRequest,
+    amount_msat: u64,
+    comment: Option<&str>,
+    zap_request: Option<&str>,
+    payer_data: Option<&LnurlPayerData>,
+) -> LnurlResult<LnurlPayInvoice> {
+    ensure_sdk!(
+        (pay_request.min_sendable..=pay_request.max_sendable).contains(&amount_msat),
+        LnurlError::InvalidAmount(format!(
+            "{amount_msat} msat is not between {} and {} msat",
+            pay_request.min_sendable, pay_request.max_sendable
+        ))
+    );
+
+    let mut url = format!(
+        "{}{}amount={amount_msat}",
+        pay_request.callback,
+        if pay_request.callback.contains('?') {
+            '&'
+        } else {
+            '?'
+        }
+    );
+    if let Some(comment) = comment.filter(|comment| !comment.is_empty()) {
+        ensure_sdk!(
+            comment.chars().count() <= usize::from(pay_request.comment_allowed),
+            LnurlError::General(format!(
+                "comment exceeds the {} characters allowed",
+                pay_request.comment_allowed
+            ))
+        );
+        url.push_str("&comment=");
+        url.extend(utf8_percent_encode(comment, NON_ALPHANUMERIC));
+    }
+    if let Some(zap_request) = zap_request {
+        url.push_str("&nostr=");
+        url.extend(utf8_percent_encode(zap_request, NON_ALPHANUMERIC));
+    }
+    let payer_data = payer_data
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| LnurlError::General(format!("failed to serialize payer data: {e}")))?;
+    if let Some(payer_data) = &payer_data {
+        url.push_str("&payerdata=");
+        url.extend(utf8_percent_encode(payer_data, NON_ALPHANUMERIC));
+    }
+
+    debug!("Requesting LNURL-pay invoice");
+    let (response, _) = rest_client.get(&url).await?;
+    if let Ok(LnurlCallbackStatus::ErrorStatus { data }) = serde_json::from_str(&response) {
+        return Err(LnurlError::EndpointError(data.reason));
+    }
+    let response: LnurlPayInvoice = serde_json::from_str(&response)
+        .map_err(|e| LnurlError::EndpointError(format!("invalid callback response: {e}")))?;
+
+    let invoice: Bolt11Invoice = response
+        .pr
+        .parse()
+        .map_err(|e| LnurlError::InvalidInvoice(format!("{e}")))?;
+    ensure_sdk!(
+        invoice.amount_milli_satoshis() == Some(amount_msat),
+        LnurlError::InvalidInvoice("amount does not match the requested amount".to_string())
+    );
+    // As per LUD-18, the payer data is appended to the metadata committed to
+    let description = match (zap_request, payer_data) {
+        (Some(zap_request), _) => zap_request.to_string(),
+        (None, Some(payer_data)) => format!("{}{payer_data}", pay_request.metadata_str),
+        (None, None) => pay_request.metadata_str.clone(),
+    };
+    let description_hash = sha256::Hash::hash(description.as_bytes());
+    ensure_sdk!(
+        matches!(invoice.description(), Bolt11InvoiceDescriptionRef::Hash(hash) if hash.0 == description_hash),
+        LnurlError::InvalidInvoice(
+            "description hash does not match the requested description".to_string()
+        )
+    );
+
+    Ok(response)
+}
+
+/// Checks whether the invoice of a LNURL-pay payment was settled, as reported by the LUD-21
+/// verify URL returned along with it. The preimage is checked against the payment hash.
+pub async fn verify_payment<C: RestClient + ?Sized>(
+    rest_client: &C,
+    verify_url: &str,
+) -> LnurlResult<LnurlPaymentVerification> {
+    let (response, _) = rest_client.get(verify_url).await?;
+    if let Ok(LnurlCallbackStatus::ErrorStatus { data }) = serde_json::from_str(&response) {
+        return Err(LnurlError::EndpointError(data.reason));
+    }
+    let verification: LnurlPaymentVerification = serde_json::from_str(&response)
+        .map_err(|e| LnurlError::EndpointError(format!("invalid verify response: {e}")))?;
+
+    if let Some(preimage) = verification
+        .preimage
+        .as_ref()
+        .filter(|_| verification.settled)
+    {
+        let invoice: Bolt11Invoice = verification
+            .pr
+            .parse()
+            .map_err(|e| LnurlError::InvalidInvoice(format!("{e}")))?;
+        let preimage = hex::decode(preimage)
+            .map_err(|_| LnurlError::General("preimage is not hex encoded".to_string()))?;
+        ensure_sdk!(
+            sha256::Hash::hash(&preimage) == *invoice.payment_hash(),
+            LnurlError::General("preimage does not match the payment hash".to_string())
+        );
+    }
+    Ok(verification)
+}
+
+/// Polls the LUD-21 verify URL until the invoice is settled or the timeout elapses, returning
+/// the last verification. On WASM, where no timer is available, the URL is checked once.
+pub async fn poll_payment_verification<C: RestClient + ?Sized>(
+    rest_client: &C,
+    verify_url: &str,
+    timeout: Duration,
+) -> LnurlResult<LnurlPaymentVerification> {
+    let deadline = Instant::now().checked_add(timeout);
+    loop {
+        let verification = verify_payment(rest_client, verify_url).await?;
+        if verification.settled
+            || cfg!(all(target_family = "wasm", target_os = "unknown"))
+            || deadline.is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Ok(verification);
+        }
+
+        debug!("LNURL-pay invoice not settled yet, polling again");
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        tokio::time::sleep(VERIFY_POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bitcoin::{
+        NetworkKind,
+        bip32::{ChildNumber, Xpriv, Xpub},
+        hashes::{Hash, HashEngine, Hmac, HmacEngine, sha256},
+        secp256k1::{Message, Secp256k1, SecretKey},
+    };
+    use lightning::{
+        bolt11_invoice::{Currency, InvoiceBuilder},
+        types::payment::PaymentSecret,
+    };
+    use serde_json::json;
+
+    use crate::{
+        input::{
+            LnurlPayRequest, LnurlPayerDataAuthRequirement, LnurlPayerDataRequest,
+            LnurlPayerDataRequirement,
+        },
+        lnurl::{
+            auth::LnurlAuthSigner,
+            error::{LnurlError, LnurlResult},
+        },
+        test_utils::mock_rest_client::{MockResponse, MockRestClient},
+    };
+
+    use super::{
+        LnurlPayerDetails, build_payer_data, parse_metadata, poll_payment_verification,
+        request_invoice, verify_payment,
+    };
+
+    const METADATA: &str = "[[\"text/plain\",\"alice\"]]";
+    const ZAP_REQUEST: &str = "{\"kind\":9734}";
+    const PREIMAGE: [u8; 32] = [4; 32];
+    const K1: &str = "18ec6d5b96db6f219baed2f188aee7359fcf5bea11bb7d5b47157519474c2222";
+
+    struct TestSigner {
+        master: Xpriv,
+    }
+
+    impl TestSigner {
+        fn new() -> Self {
+            Self {
+                master: Xpriv::new_master(NetworkKind::Main, &[7; 32]).unwrap(),
+            }
+        }
+
+        fn derive(&self, derivation_path: &[ChildNumber]) -> Xpriv {
+            self.master
+                .derive_priv(&Secp256k1::new(), &derivation_path)
+                .unwrap()
+        }
+    }
+
+    #[breez_sdk_macros::async_trait]
+    impl LnurlAuthSigner for TestSigner {
+        async fn derive_bip32_pub_key(
+            &self,
+            derivation_path: &[ChildNumber],
+        ) -> LnurlResult<Vec<u8>> {
+            let xpriv = self.derive(derivation_path);
+            Ok(Xpub::from_priv(&Secp256k1::new(), &xpriv).encode().to_vec())
+        }
+
+        async fn sign_ecdsa(
+            &self,
+            msg: &[u8],
+            derivation_path: &[ChildNumber],
+        ) -> LnurlResult<Vec<u8>> {
+            let xpriv = self.derive(derivation_path);
+            let msg = Message::from_digest_slice(msg).unwrap();
+            Ok(Secp256k1::new()
+                .sign_ecdsa(&msg, &xpriv.private_key)
+                .serialize_der()
+                .to_vec())
+        }
+
+        async fn hmac_sha256(
+            &self,
+            key_derivation_path: &[ChildNumber],
+            input: &[u8],
+        ) -> LnurlResult<Vec<u8>> {
+            let xpriv = self.derive(key_derivation_path);
+            let mut engine = HmacEngine::<sha256::Hash>::new(&xpriv.private_key.secret_bytes());
+            engine.input(input);
+            Ok(Hmac::<sha256::Hash>::from_engine(engine)
+                .to_byte_array()
+                .to_vec())
+        }
+    }
+
+    fn pay_request() -> LnurlPayRequest {
+        LnurlPayRequest {
+            callback: "https://example.com/lnurlp/alice/callback".to_string(),
+            min_sendable: 1_000,
+            max_sendable: 1_000_000,
+            metadata_str: METADATA.to_string(),
+            metadata: parse_metadata(METADATA).unwrap(),
+            comment_allowed: 10,
+            domain: "example.com".to_string(),
+            url: "https://example.com/.well-known/lnurlp/alice".to_string(),
+            allows_nostr: true,
+            nostr_pubkey: None,
+            payer_data: None,
+        }
+    }
+
+    fn payer_data_request(name_mandatory: bool) -> LnurlPayerDataRequest {
+        LnurlPayerDataRequest {
+            name: Some(LnurlPayerDataRequirement {
+                mandatory: name_mandatory,
+            }),
+            pubkey: Some(LnurlPayerDataRequirement { mandatory: false }),
+            identifier: None,
+            email: Some(LnurlPayerDataRequirement { mandatory: false }),
+            auth: Some(LnurlPayerDataAuthRequirement {
+                mandatory: true,
+                k1: K1.to_string(),
+            }),
+        }
+    }
+
+    fn invoice(amount_msat: u64, description: &str) -> String {
+        let key = SecretKey::from_slice(&[1; 32]).unwrap();
+        InvoiceBuilder::new(Currency::Bitcoin)
+            .description_hash(sha256::Hash::hash(description.as_bytes()))
+            .payment_hash(sha256::Hash::hash(&PREIMAGE))
+            .payment_secret(PaymentSecret([2; 32]))
+            .duration_since_epoch(Duration::from_secs(1_700_000_000))
+            .min_final_cltv_expiry_delta(144)
+            .amount_milli_satoshis(amount_msat)
+            .build_signed(|hash| Secp256k1::new().sign_ecdsa_recoverable(hash, &key))
+            .unwrap()
+            .to_string()
+    }
+
+    fn callback_response(invoice: &str) -> MockResponse {
+        MockResponse::new(200, json!({ "pr": invoice, "routes": [] }).to_string())
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_request_invoice() {
+        let rest_client = MockRestClient::new();
+        let expected = invoice(21_000, METADATA);
+        rest_client.add_response(MockResponse::new(
+            200,
+            json!({ "pr": expected, "routes": [], "verify": "https://example.com/verify/1" })
+                .to_string(),
+        ));
+        let invoice = request_invoice(&rest_client, &pay_request(), 21_000, Some("gm"), None, None)
+            .await
+            .unwrap();
+        assert_eq!(invoice.pr, expected);
+        assert_eq!(
+            invoice.verify.as_deref(),
+            Some("https://example.com/verify/1")
+        );
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_request_zap_invoice() {
+        let rest_client = MockRestClient::new();
+        let expected = invoice(21_000, ZAP_REQUEST);
+        rest_client
+            .add_response(callback_response(&expected))
+            .add_response(callback_response(&invoice(21_000, METADATA)));
+        let invoice = request_invoice(
+            &rest_client,
+            &pay_request(),
+            21_000,
+            None,
+            Some(ZAP_REQUEST),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(invoice.pr, expected);
+        assert!(invoice.verify.is_none());
+
+        // A zap invoice must commit to the zap request rather than the metadata
+        assert!(matches!(
+            request_invoice(
+                &rest_client,
+                &pay_request(),
+                21_000,
+                None,
+                Some(ZAP_REQUEST),
+                None
+            )
+            .await,
+            Err(LnurlError::InvalidInvoice(_))
+        ));
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_request_invoice_errors() {
+        let rest_client = MockRestClient::new();
+        assert!(matches!(
+            request_invoice(&rest_client, &pay_request(), 999, None, None, None).await,
+            Err(LnurlError::InvalidAmount(_))
+        ));
+        assert!(matches!(
+            request_invoice(
+                &rest_client,
+                &pay_request(),
+                21_000,
+                Some("a comment that is too long"),
+                None,
+                None
+            )
+            .await,
+            Err(LnurlError::General(_))
+        ));
+
+        rest_client
+            .add_response(MockResponse::new(
+                200,
+                json!({ "status": "ERROR", "reason": "no route" }).to_string(),
+            ))
+            .add_response(callback_response(&invoice(20_000, METADATA)));
+        assert!(matches!(
+            request_invoice(&rest_client, &pay_request(), 21_000, None, None, None).await,
+            Err(LnurlError::EndpointError(reason)) if reason == "no route"
+        ));
+        assert!(matches!(
+            request_invoice(&rest_client, &pay_request(), 21_000, None, None, None).await,
+            Err(LnurlError::InvalidInvoice(_))
+        ));
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_build_payer_data() {
+        let signer = TestSigner::new();
+        let details = LnurlPayerDetails {
+            name: Some("Alice".to_string()),
+            email: None,
+            identifier: Some("alice@example.com".to_string()),
+        };
+
+        // Nothing is shared with endpoints not asking for payer data
+        let payer_data = build_payer_data(&signer, &pay_request(), &details)
+            .await
+            .unwrap();
+        assert!(payer_data.is_none());
+
+        let mut pay_request = pay_request();
+        pay_request.payer_data = Some(payer_data_request(true));
+        let payer_data = build_payer_data(&signer, &pay_request, &details)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(payer_data.name.as_deref(), Some("Alice"));
+        assert!(payer_data.email.is_none());
+        // The identifier was not asked for
+        assert!(payer_data.identifier.is_none());
+        let auth = payer_data.auth.unwrap();
+        assert_eq!(auth.k1, K1);
+        assert_eq!(payer_data.pubkey, Some(auth.key.clone()));
+        let sig = bitcoin::secp256k1::ecdsa::Signature::from_der(&hex::decode(&auth.sig).unwrap())
+            .unwrap();
+        let key: bitcoin::secp256k1::PublicKey = auth.key.parse().unwrap();
+        let msg = Message::from_digest_slice(&hex::decode(K1).unwrap()).unwrap();
+        assert!(Secp256k1::new().verify_ecdsa(&msg, &sig, &key).is_ok());
+
+        let details = LnurlPayerDetails::default();
+        assert!(matches!(
+            build_payer_data(&signer, &pay_request, &details).await,
+            Err(LnurlError::MissingPayerData(field)) if field == "name"
+        ));
+        pay_request.payer_data = Some(payer_data_request(false));
+        let payer_data = build_payer_data(&signer, &pay_request, &details)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(payer_data.name.is_none());
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_request_invoice_with_payer_data() {
+        let signer = TestSigner::new();
+        let mut pay_request = pay_request();
+        pay_request.payer_data = Some(payer_data_request(false));
+        let payer_data = build_payer_data(&signer, &pay_request, &LnurlPayerDetails::default())
+            .await
+            .unwrap()
+            .unwrap();
+        let serialized = serde_json::to_string(&payer_data).unwrap();
+
+        let rest_client = MockRestClient::new();
+        let expected = invoice(21_000, &format!("{METADATA}{serialized}"));
+        rest_client
+            .add_response(callback_response(&expected))
+            .add_response(callback_response(&invoice(21_000, METADATA)));
+        let invoice = request_invoice(
+            &rest_client,
+            &pay_request,
+            21_000,
+            None,
+            None,
+            Some(&payer_data),
+        )
+        .await
+        .unwrap();
+        assert_eq!(invoice.pr, expected);
+
+        // The invoice must commit to the payer data
+        assert!(matches!(
+            request_invoice(
+                &rest_client,
+                &pay_request,
+                21_000,
+                None,
+                None,
+                Some(&payer_data)
+            )
+            .await,
+            Err(LnurlError::InvalidInvoice(_))
+        ));
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_verify_payment() {
+        let rest_client = MockRestClient::new();
+        let pr = invoice(21_000, METADATA);
+        rest_client
+            .add_response(MockResponse::new(
+                200,
+                json!({ "status": "OK", "settled": false, "preimage": null, "pr": pr })
+                    .to_string(),
+            ))
+            .add_response(MockResponse::new(
+                200,
+                json!({ "status": "OK", "settled": true, "preimage": hex::encode(PREIMAGE), "pr": pr })
+                    .to_string(),
+            ))
+            .add_response(MockResponse::new(
+                200,
+                json!({ "status": "OK", "settled": true, "preimage": hex::encode([5; 32]), "pr": pr })
+                    .to_string(),
+            ))
+            .add_response(MockResponse::new(
+                200,
+                json!({ "status": "ERROR", "reason": "Not found" }).to_string(),
+            ));
+
+        let verify_url = "https://example.com/verify/1";
+        let verification = verify_payment(&rest_client, verify_url).await.unwrap();
+        assert!(!verification.settled);
+        assert!(verification.preimage.is_none());
+        let verification = verify_payment(&rest_client, verify_url).await.unwrap();
+        assert!(verification.settled);
+        assert_eq!(verification.preimage, Some(hex::encode(PREIMAGE)));
+        assert!(matches!(
+            verify_payment(&rest_client, verify_url).await,
+            Err(LnurlError::General(_))
+        ));
+        assert!(matches!(
+            verify_payment(&rest_client, verify_url).await,
+            Err(LnurlError::EndpointError(reason)) if reason == "Not found"
+        ));
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_poll_payment_verification() {
+        let rest_client = MockRestClient::new();
+        let pr = invoice(21_000, METADATA);
+        rest_client.add_response(MockResponse::new(
+            200,
+            json!({ "status": "OK", "settled": false, "preimage": null, "pr": pr }).to_string(),
+        ));
+
+        // An elapsed timeout returns the unsettled verification
+        let verification =
+            poll_payment_verification(&rest_client, "https://example.com/verify/1", Duration::ZERO)
+                .await
+                .unwrap();
+        assert!(!verification.settled);
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_parse_metadata() {
+        let metadata = parse_metadata(METADATA).unwrap();
+        assert_eq!(metadata.description, "alice");
+        assert!(metadata.long_description.is_none());
+        assert!(metadata.image.is_none());
+
+        let metadata = parse_metadata(
+            &json!([
+                ["text/plain", "Pay to alice@example.com"],
+                ["text/long-desc", "Alice's coffee shop"],
+                ["image/jpeg;base64", "/9j/4AAQSkZJRg=="],
+                ["text/identifier", "alice@example.com"],
+                ["text/email", "alice@mail.example.com"],
+                ["text/vnd.unknown", {"ignored": true}],
+            ])
+            .to_string(),
+        )
+        .unwrap();
+        assert_eq!(metadata.description, "Pay to alice@example.com");
+        assert_eq!(
+            metadata.long_description.as_deref(),
+            Some("Alice's coffee shop")
+        );
+        let image = metadata.image.unwrap();
+        assert_eq!(image.mime_type, "image/jpeg");
+        assert_eq!(image.data, "/9j/4AAQSkZJRg==");
+        assert_eq!(metadata.identifier.as_deref(), Some("alice@example.com"));
+        assert_eq!(metadata.email.as_deref(), Some("alice@mail.example.com"));
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_pay_request_serde_round_trip() {
+        let serialized = serde_json::to_value(pay_request()).unwrap();
+        assert_eq!(serialized["metadata"], METADATA);
+
+        // The parsed metadata is skipped and has to be parsed again
+        let deserialized: LnurlPayRequest = serde_json::from_value(serialized).unwrap();
+        assert_eq!(deserialized.metadata_str, METADATA);
+        assert!(deserialized.metadata.description.is_empty());
+        let metadata = parse_metadata(&deserialized.metadata_str).unwrap();
+        assert_eq!(metadata.description, "alice");
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_parse_invalid_metadata() {
+        for metadata_str in [
+            "not json".to_string(),
+            json!({ "text/plain": "alice" }).to_string(),
+            json!([]).to_string(),
+            json!([["text/long-desc", "no description"]]).to_string(),
+            json!([["text/plain", "alice"], ["text/plain", "bob"]]).to_string(),
+            json!([["text/plain"]]).to_string(),
+            json!([["text/plain", 21]]).to_string(),
+            json!([["text/plain", "alice"], ["image/png;base64", "not base64!"]]).to_string(),
+        ] {
+            assert!(
+                matches!(
+                    parse_metadata(&metadata_str),
+                    Err(LnurlError::InvalidMetadata(_))
+                ),
+                "{metadata_str} should be invalid"
+            );
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use bech32::{Bech32, Hrp};
+use std::str::FromStr;
+
+use bitcoin::{
+    NetworkKind,
+    bip32::{DerivationPath, Xpriv},
+    hashes::{Hash, sha256},
+    secp256k1::{Keypair, Message, Secp256k1, SecretKey},
+};
+use maybe_sync::{MaybeSend, MaybeSync};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::{ensure_sdk, input::LnurlPayRequest};
+
+use super::error::{LnurlError, LnurlResult};
+
+/// The kind of nostr zap request events, see
+/// <https://github.com/nostr-protocol/nips/blob/master/57.md#appendix-a-zap-request-event>
+pub const ZAP_REQUEST_KIND: u16 = 9734;
+
+/// The path nostr keys are derived at from a seed, see
+/// <https://github.com/nostr-protocol/nips/blob/master/06.md>
+const NIP06_DERIVATION_PATH: &str = "m/44'/1237'/0'/0/0";
+
+/// Makes a LNURL-pay payment a nostr zap, see NIP-57.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct Zap {
+    /// The hex encoded nostr public key of the recipient
+    pub recipient_pubkey: String,
+    /// The relays the recipient publishes the zap receipt to
+    pub relays: Vec<String>,
+    /// The hex encoded id of the zapped event, when zapping a note
+    pub event_id: Option<String>,
+    /// The hex encoded nostr secret key signing the zap request. When not set, the key derived
+    /// from the seed as per NIP-06 is used.
+    pub nostr_secret_key: Option<String>,
+}
+
+/// Signs nostr events with BIP-340 Schnorr signatures.
+#[breez_sdk_macros::async_trait]
+pub trait NostrSigner: MaybeSend + MaybeSync {
+    /// The hex encoded x-only public key
+    async fn public_key(&self) -> LnurlResult<String>;
+
+    /// Signs the event id, returning the hex encoded signature
+    async fn sign_event_id(&self, id: &[u8; 32]) -> LnurlResult<String>;
+}
+
+/// Signs nostr events with a given secret key.
+pub struct NostrKeySigner {
+    keypair: Keypair,
+}
+
+impl NostrKeySigner {
+    pub fn from_secret_key(secret_key: &str) -> LnurlResult<Self> {
+        let secret_key =
+            SecretKey::from_slice(&hex::decode(secret_key).map_err(|_| {
+                LnurlError::General("nostr secret key is not hex encoded".to_string())
+            })?)
+            .map_err(|e| LnurlError::General(format!("invalid nostr secret key: {e}")))?;
+        Ok(Self {
+            keypair: Keypair::from_secret_key(&Secp256k1::signing_only(), &secret_key),
+        })
+    }
+
+    /// Derives the nostr key of the seed as per NIP-06.
+    pub fn from_seed(seed: &[u8]) -> LnurlResult<Self> {
+        let secp = Secp256k1::signing_only();
+        let path = DerivationPath::from_str(NIP06_DERIVATION_PATH)
+            .map_err(|e| LnurlError::General(e.to_string()))?;
+        let xpriv = Xpriv::new_master(NetworkKind::Main, seed)
+            .and_then(|master| master.derive_priv(&secp, &path))
+            .map_err(|e| LnurlError::General(format!("failed to derive nostr key: {e}")))?;
+        Ok(Self {
+            keypair: xpriv.to_keypair(&secp),
+        })
+    }
+}
+
+#[breez_sdk_macros::async_trait]
+impl NostrSigner for NostrKeySigner {
+    async fn public_key(&self) -> LnurlResult<String> {
+        Ok(self.keypair.x_only_public_key().0.to_string())
+    }
+
+    async fn sign_event_id(&self, id: &[u8; 32]) -> LnurlResult<String> {
+        let signature = Secp256k1::signing_only()
+            .sign_schnorr_no_aux_rand(&Message::from_digest(*id), &self.keypair);
+        Ok(signature.to_string())
+    }
+}
+
+/// Builds and signs the kind-9734 zap request of the payment, to be passed to the LNURL-pay
+/// callback as the `nostr` parameter. The invoice must commit to the returned JSON in its
+/// description hash.
+pub async fn build_zap_request<S: NostrSigner + ?Sized>(
+    signer: &S,
+    pay_request: &LnurlPayRequest,
+    zap: &Zap,
+    amount_msat: u64,
+    comment: Option<&str>,
+) -> LnurlResult<String> {
+    ensure_sdk!(
+        pay_request.allows_nostr && pay_request.nostr_pubkey.is_some(),
+        LnurlError::ZapsNotSupported
+    );
+    ensure_sdk!(
+        !zap.relays.is_empty(),
+        LnurlError::General("a zap request needs at least one relay".to_string())
+    );
+    ensure_sdk!(
+        is_hex_id(&zap.recipient_pubkey),
+        LnurlError::General("invalid recipient pubkey".to_string())
+    );
+
+    let mut relays = vec!["relays".to_string()];
+    relays.extend(zap.relays.iter().cloned());
+    let lnurl = bech32::encode::<Bech32>(Hrp::parse_unchecked("lnurl"), pay_request.url.as_bytes())
+        .map_err(|e| LnurlError::General(e.to_string()))?;
+    let mut tags = vec![
+        relays,
+        vec!["amount".to_string(), amount_msat.to_string()],
+        vec!["lnurl".to_string(), lnurl],
+        vec!["p".to_string(), zap.recipient_pubkey.clone()],
+    ];
+    if let Some(event_id) = &zap.event_id {
+        ensure_sdk!(
+            is_hex_id(event_id),
+            LnurlError::General("invalid event id".to_string())
+        );
+        tags.push(vec!["e".to_string(), event_id.clone()]);
+    }
+
+    let pubkey = signer.public_key().await?;
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| LnurlError::General(e.to_string()))?
+        .as_secs();
+    let content = comment.unwrap_or_default();
+    // The event id commits to the serialized event, see NIP-01
+    let serialized = json!([0, pubkey, created_at, ZAP_REQUEST_KIND, tags, content]).to_string();
+    let id = sha256::Hash::hash(serialized.as_bytes()).to_byte_array();
+    let sig = signer.sign_event_id(&id).await?;
+
+    Ok(json!({
+        "id": hex::encode(id),
+        "pubkey": pubkey,
+        "created_at": created_at,
+        "kind": ZAP_REQUEST_KIND,
+        "tags": tags,
+        "content": content,
+        "sig": sig,
+    })
+    .to_string())
+}
+
+/// Nostr public keys and event ids are hex encoded 32 byte values.
+fn is_hex_id(value: &str) -> bool {
+    value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{
+        hashes::{Hash, sha256},
+        secp256k1::{Message, Secp256k1, XOnlyPublicKey, schnorr::Signature},
+    };
+    use serde_json::{Value, json};
+
+    use crate::{
+        input::{LnurlPayMetadata, LnurlPayRequest},
+        lnurl::error::LnurlError,
+    };
+
+    use super::{NostrKeySigner, NostrSigner, Zap, build_zap_request};
+
+    const SECRET_KEY: &str = "0000000000000000000000000000000000000000000000000000000000000003";
+    const PUBKEY: &str = "f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9";
+    const RECIPIENT_PUBKEY: &str =
+        "32e1827635450ebb3c5a7d12c1f8e7b2b514439ac10a67eef3d9fd9c5c68e245";
+
+    fn pay_request(allows_nostr: bool) -> LnurlPayRequest {
+        LnurlPayRequest {
+            callback: "https://example.com/lnurlp/alice/callback".to_string(),
+            min_sendable: 1_000,
+            max_sendable: 1_000_000,
+            metadata_str: "[[\"text/plain\",\"alice\"]]".to_string(),
+            metadata: LnurlPayMetadata {
+                description: "alice".to_string(),
+                ..Default::default()
+            },
+            comment_allowed: 0,
+            domain: "example.com".to_string(),
+            url: "https://example.com/.well-known/lnurlp/alice".to_string(),
+            allows_nostr,
+            nostr_pubkey: allows_nostr.then(|| RECIPIENT_PUBKEY.to_string()),
+            payer_data: None,
+        }
+    }
+
+    fn zap() -> Zap {
+        Zap {
+            recipient_pubkey: RECIPIENT_PUBKEY.to_string(),
+            relays: vec!["wss://relay.example.com".to_string()],
+            event_id: None,
+            nostr_secret_key: None,
+        }
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_build_zap_request() {
+        let signer = NostrKeySigner::from_secret_key(SECRET_KEY).unwrap();
+        let zap_request =
+            build_zap_request(&signer, &pay_request(true), &zap(), 21_000, Some("gm"))
+                .await
+                .unwrap();
+
+        let event: Value = serde_json::from_str(&zap_request).unwrap();
+        assert_eq!(event["kind"], 9734);
+        assert_eq!(event["pubkey"], PUBKEY);
+        assert_eq!(event["content"], "gm");
+        assert_eq!(
+            event["tags"],
+            json!([
+                ["relays", "wss://relay.example.com"],
+                ["amount", "21000"],
+                [
+                    "lnurl",
+                    "lnurl1dp68gurn8ghj7etcv9khqmr99e3k7mf09emk2mrv944kummhdchkcmn4wfk8qtmpd35kxeg9saevq"
+                ],
+                ["p", RECIPIENT_PUBKEY],
+            ])
+        );
+
+        // The id and signature verify against the serialized event
+        let serialized = json!([
+            0,
+            event["pubkey"],
+            event["created_at"],
+            event["kind"],
+            event["tags"],
+            event["content"]
+        ])
+        .to_string();
+        let id = sha256::Hash::hash(serialized.as_bytes()).to_byte_array();
+        assert_eq!(event["id"], hex::encode(id));
+        let signature: Signature = event["sig"].as_str().unwrap().parse().unwrap();
+        let pubkey: XOnlyPublicKey = PUBKEY.parse().unwrap();
+        assert!(
+            Secp256k1::verification_only()
+                .verify_schnorr(&signature, &Message::from_digest(id), &pubkey)
+                .is_ok()
+        );
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_build_zap_request_errors() {
+        let signer = NostrKeySigner::from_secret_key(SECRET_KEY).unwrap();
+        assert!(matches!(
+            build_zap_request(&signer, &pay_request(false), &zap(), 21_000, None).await,
+            Err(LnurlError::ZapsNotSupported)
+        ));
+
+        let mut no_relays = zap();
+        no_relays.relays.clear();
+        assert!(matches!(
+            build_zap_request(&signer, &pay_request(true), &no_relays, 21_000, None).await,
+            Err(LnurlError::General(_))
+        ));
+
+        let mut invalid_event = zap();
+        invalid_event.event_id = Some("note1".to_string());
+        assert!(matches!(
+            build_zap_request(&signer, &pay_request(true), &invalid_event, 21_000, None).await,
+            Err(LnurlError::General(_))
+        ));
+
+        assert!(NostrKeySigner::from_secret_key("nsec1").is_err());
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_nostr_key_from_seed() {
+        // The NIP-06 test vector of the mnemonic "leader monkey parrot ring guide accident before
+        // fence cannon height naive bean"
+        let seed = hex::decode(
+            "173b9c5f0d165502d08a4d122b2c9bf1e33e27806eac119713600a263c1241101dc55fb7cffb8f48a59b19a5ba65b037904f907bb8d08eb5bff8a17e85c2ee93",
+        )
+        .unwrap();
+        let signer = NostrKeySigner::from_seed(&seed).unwrap();
+        assert_eq!(
+            signer.public_key().await.unwrap(),
+            "17162c921dc4d2518f9a101db33695df1afb56ab82f5ff3e5da6eec3ca5cd917"
+        );
+    }
+}
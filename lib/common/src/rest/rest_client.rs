@@ -36,6 +36,18 @@ impl ReqwestRestClient {
             .map_err(Into::<ServiceConnectivityError>::into)?;
         Ok(ReqwestRestClient { client })
     }
+
+    /// Creates a client resolving hosts with the given resolver, sharing its cache.
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    pub fn with_dns_resolver(
+        resolver: crate::dns::Resolver,
+    ) -> Result<Self, ServiceConnectivityError> {
+        let client = Client::builder()
+            .dns_resolver(std::sync::Arc::new(resolver))
+            .build()
+            .map_err(Into::<ServiceConnectivityError>::into)?;
+        Ok(ReqwestRestClient { client })
+    }
 }
 
 #[breez_sdk_macros::async_trait]
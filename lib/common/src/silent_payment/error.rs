@@ -0,0 +1,20 @@
+use thiserror::Error;
+
+pub type SilentPaymentResult<T, E = SilentPaymentError> = Result<T, E>;
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
+pub enum SilentPaymentError {
+    #[error("no eligible inputs to derive silent payment outputs from")]
+    NoInputs,
+    #[error("invalid silent payment address: {0}")]
+    InvalidAddress(String),
+    #[error("silent payment error: {0}")]
+    General(String),
+}
+
+impl From<bitcoin::secp256k1::Error> for SilentPaymentError {
+    fn from(err: bitcoin::secp256k1::Error) -> Self {
+        Self::General(err.to_string())
+    }
+}
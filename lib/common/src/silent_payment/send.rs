@@ -0,0 +1,337 @@
+use std::{collections::HashMap, str::FromStr};
+
+use bitcoin::{
+    OutPoint,
+    consensus::serialize,
+    hashes::{Hash, HashEngine, sha256},
+    secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey, XOnlyPublicKey},
+};
+
+use crate::input::SilentPaymentAddress;
+
+use super::error::{SilentPaymentError, SilentPaymentResult};
+
+const INPUTS_TAG: &[u8] = b"BIP0352/Inputs";
+const SHARED_SECRET_TAG: &[u8] = b"BIP0352/SharedSecret";
+
+/// An input of the transaction paying to silent payment addresses, see
+/// <https://github.com/bitcoin/bips/blob/master/bip-0352.mediawiki#inputs-for-shared-secret-derivation>
+///
+/// Only P2TR, P2WPKH, P2SH-P2WPKH and P2PKH inputs are eligible. Other inputs can be spent in the
+/// same transaction, but must not be passed to [`derive_silent_payment_outputs`].
+#[derive(Clone, Debug)]
+pub struct SilentPaymentInput {
+    pub outpoint: OutPoint,
+    pub secret_key: SecretKey,
+    /// Whether the input is a taproot key path spend, in which case `secret_key` is the tweaked
+    /// output key.
+    pub is_taproot: bool,
+}
+
+/// Derives the taproot output keys paying to the given silent payment addresses, as per
+/// <https://github.com/bitcoin/bips/blob/master/bip-0352.mediawiki#creating-outputs>
+///
+/// The returned output keys are in the same order as the recipients. Paying the same address
+/// multiple times results in distinct outputs.
+pub fn derive_silent_payment_outputs(
+    inputs: &[SilentPaymentInput],
+    recipients: &[SilentPaymentAddress],
+) -> SilentPaymentResult<Vec<XOnlyPublicKey>> {
+    let secp = Secp256k1::new();
+    let smallest_outpoint = inputs
+        .iter()
+        .map(|input| serialize(&input.outpoint))
+        .min()
+        .ok_or(SilentPaymentError::NoInputs)?;
+
+    let mut input_secret: Option<SecretKey> = None;
+    for input in inputs {
+        let mut secret_key = input.secret_key;
+        if input.is_taproot && secret_key.x_only_public_key(&secp).1.to_u8() == 1 {
+            secret_key = secret_key.negate();
+        }
+        input_secret = Some(match input_secret {
+            Some(sum) => sum.add_tweak(&Scalar::from(secret_key))?,
+            None => secret_key,
+        });
+    }
+    let input_secret = input_secret.ok_or(SilentPaymentError::NoInputs)?;
+
+    let mut engine = tagged_hash_engine(INPUTS_TAG);
+    engine.input(&smallest_outpoint);
+    engine.input(&input_secret.public_key(&secp).serialize());
+    let input_hash = Scalar::from_be_bytes(sha256::Hash::from_engine(engine).to_byte_array())
+        .map_err(|e| SilentPaymentError::General(e.to_string()))?;
+    let input_secret = input_secret.mul_tweak(&input_hash)?;
+
+    // Outputs are derived per scan key, with a counter for each output to the same scan key.
+    let mut shared_secrets: HashMap<PublicKey, (PublicKey, u32)> = HashMap::new();
+    let mut outputs = Vec::with_capacity(recipients.len());
+    for recipient in recipients {
+        let scan_pubkey = parse_pubkey(&recipient.scan_pubkey)?;
+        let spend_pubkey = parse_pubkey(&recipient.spend_pubkey)?;
+        let (shared_secret, counter) = match shared_secrets.get_mut(&scan_pubkey) {
+            Some(entry) => entry,
+            None => shared_secrets.entry(scan_pubkey).or_insert((
+                scan_pubkey.mul_tweak(&secp, &Scalar::from(input_secret))?,
+                0,
+            )),
+        };
+
+        let mut engine = tagged_hash_engine(SHARED_SECRET_TAG);
+        engine.input(&shared_secret.serialize());
+        engine.input(&counter.to_be_bytes());
+        let tweak = Scalar::from_be_bytes(sha256::Hash::from_engine(engine).to_byte_array())
+            .map_err(|e| SilentPaymentError::General(e.to_string()))?;
+        *counter = counter
+            .checked_add(1)
+            .ok_or_else(|| SilentPaymentError::General("too many outputs".to_string()))?;
+
+        let output = spend_pubkey.add_exp_tweak(&secp, &tweak)?;
+        outputs.push(output.x_only_public_key().0);
+    }
+
+    Ok(outputs)
+}
+
+fn parse_pubkey(pubkey: &str) -> SilentPaymentResult<PublicKey> {
+    PublicKey::from_str(pubkey).map_err(|e| SilentPaymentError::InvalidAddress(e.to_string()))
+}
+
+/// Creates a BIP-340 tagged hash engine.
+fn tagged_hash_engine(tag: &[u8]) -> sha256::HashEngine {
+    let tag_hash = sha256::Hash::hash(tag);
+    let mut engine = sha256::Hash::engine();
+    engine.input(tag_hash.as_ref());
+    engine.input(tag_hash.as_ref());
+    engine
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitcoin::{
+        OutPoint, Txid,
+        secp256k1::{Secp256k1, SecretKey},
+    };
+
+    use super::{SilentPaymentInput, derive_silent_payment_outputs};
+    use crate::{
+        input::{PaymentRequestSource, SilentPaymentAddress},
+        network::BitcoinNetwork,
+        silent_payment::error::SilentPaymentError,
+    };
+
+    const TXID_1: &str = "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16";
+    const TXID_2: &str = "a1075db55d416d3ca199f55b6084e2115b9345e16c5cf302fc80e9d5fbf5d48d";
+    const KEY_1: &str = "eadc78165ff1f8ea94ad7cfdc54990738a4c53f6e0507b42154201b8e5dff3b1";
+    const KEY_2: &str = "93f5ed907ad5b2bdbbdcb5d9116ebc0a4e1f92f910d5260237fa45a9408aad16";
+    const TAPROOT_EVEN_KEY: &str =
+        "fc8716a97a48ba9a05a98ae47b5cd201a25a7fd5d8b73c203c5f7b6b6b3b6ad7";
+    const TAPROOT_ODD_KEY: &str =
+        "1d37787c2b7116ee983e9f9c13269df29091b391c04db94239e0d2bc2182c3bf";
+    const NON_TAPROOT_KEY: &str =
+        "8d4751f6e8a3586880fb66c19ae277969bd5aa06f61c4ee2f1e2486efdf666d3";
+    /// Spend public keys of the test vector receiver labeled with `m = 2` and `m = 3`.
+    const LABEL_2_SPEND_KEY: &str =
+        "0259352add837b6686e8d22b87017814a46b3ad308702167c65bd5c8599cd28d1c";
+    const LABEL_3_SPEND_KEY: &str =
+        "0208a144a18433a83f633c822c1bf5ee4c8c8e24601d6ca75e20a7dc57a0ff9280";
+
+    fn input(txid: &str, vout: u32, secret_key: &str, is_taproot: bool) -> SilentPaymentInput {
+        SilentPaymentInput {
+            outpoint: OutPoint::new(Txid::from_str(txid).unwrap(), vout),
+            secret_key: SecretKey::from_str(secret_key).unwrap(),
+            is_taproot,
+        }
+    }
+
+    /// The receiver of the BIP-352 sending test vectors.
+    fn recipient() -> SilentPaymentAddress {
+        let secp = Secp256k1::new();
+        let spend_key =
+            SecretKey::from_str("9d6ad855ce3417ef84e836892e5a56392bfba05fa5d97ccea30e266f540e08b3")
+                .unwrap();
+        labeled_recipient(&spend_key.public_key(&secp).to_string())
+    }
+
+    /// The receiver of the BIP-352 sending test vectors, paid through a labeled address with the
+    /// given spend public key.
+    fn labeled_recipient(spend_pubkey: &str) -> SilentPaymentAddress {
+        let secp = Secp256k1::new();
+        let scan_key =
+            SecretKey::from_str("0f694e068028a717f8af6b9411f9a133dd3565258714cc226594b34db90c1f2c")
+                .unwrap();
+        SilentPaymentAddress {
+            address: "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc9pkqwv".to_string(),
+            network: BitcoinNetwork::Bitcoin,
+            scan_pubkey: scan_key.public_key(&secp).to_string(),
+            source: PaymentRequestSource::default(),
+            spend_pubkey: spend_pubkey.to_string(),
+            version: 0,
+        }
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_bip_352_sending_vectors() {
+        let vectors = [
+            (
+                "simple send: two inputs",
+                vec![
+                    input(TXID_1, 0, KEY_1, false),
+                    input(TXID_2, 0, KEY_2, false),
+                ],
+                "3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1",
+            ),
+            (
+                "simple send: two inputs, order reversed",
+                vec![
+                    input(TXID_2, 0, KEY_2, false),
+                    input(TXID_1, 0, KEY_1, false),
+                ],
+                "3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1",
+            ),
+            (
+                "simple send: two inputs from the same transaction",
+                vec![
+                    input(TXID_1, 3, KEY_1, false),
+                    input(TXID_1, 7, KEY_2, false),
+                ],
+                "79e71baa2ba3fc66396de3a04f168c7bf24d6870ec88ca877754790c1db357b6",
+            ),
+            (
+                "outpoint ordering byte-lexicographically vs. vout-integer",
+                vec![
+                    input(TXID_1, 1, KEY_1, false),
+                    input(TXID_1, 256, KEY_2, false),
+                ],
+                "a85ef8701394b517a4b35217c4bd37ac01ebeed4b008f8d0879f9e09ba95319c",
+            ),
+            (
+                "single recipient: multiple UTXOs from the same public key",
+                vec![
+                    input(TXID_1, 0, KEY_1, false),
+                    input(TXID_2, 0, KEY_1, false),
+                ],
+                "548ae55c8eec1e736e8d3e520f011f1f42a56d166116ad210b3937599f87f566",
+            ),
+            (
+                "single recipient: taproot only inputs with even y-values",
+                vec![
+                    input(TXID_1, 0, KEY_1, true),
+                    input(TXID_2, 0, TAPROOT_EVEN_KEY, true),
+                ],
+                "de88bea8e7ffc9ce1af30d1132f910323c505185aec8eae361670421e749a1fb",
+            ),
+            (
+                "single recipient: taproot only with mixed even/odd y-values",
+                vec![
+                    input(TXID_1, 0, KEY_1, true),
+                    input(TXID_2, 0, TAPROOT_ODD_KEY, true),
+                ],
+                "77cab7dd12b10259ee82c6ea4b509774e33e7078e7138f568092241bf26b99f1",
+            ),
+            (
+                "single recipient: taproot input with even y-value and non-taproot input",
+                vec![
+                    input(TXID_1, 0, KEY_1, true),
+                    input(TXID_2, 0, NON_TAPROOT_KEY, false),
+                ],
+                "30523cca96b2a9ae3c98beb5e60f7d190ec5bc79b2d11a0b2d4d09a608c448f0",
+            ),
+            (
+                "single recipient: taproot input with odd y-value and non-taproot input",
+                vec![
+                    input(TXID_1, 0, TAPROOT_ODD_KEY, true),
+                    input(TXID_2, 0, NON_TAPROOT_KEY, false),
+                ],
+                "359358f59ee9e9eec3f00bdf4882570fd5c182e451aa2650b788544aff012a3a",
+            ),
+        ];
+
+        for (name, inputs, expected) in vectors {
+            let outputs = derive_silent_payment_outputs(&inputs, &[recipient()]).unwrap();
+            assert_eq!(outputs.len(), 1, "{name}");
+            assert_eq!(outputs[0].to_string(), expected, "{name}");
+        }
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_multiple_outputs_to_same_recipient() {
+        let inputs = [
+            input(TXID_1, 0, KEY_1, false),
+            input(TXID_2, 0, KEY_2, false),
+        ];
+        let outputs = derive_silent_payment_outputs(&inputs, &[recipient(), recipient()]).unwrap();
+        let outputs: Vec<String> = outputs.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            outputs,
+            [
+                "3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1",
+                "0ffe0b3d72d66b785e1a7ad416edcc22b951293b1507aa04850e890b002c60f1",
+            ]
+        );
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_labeled_recipients() {
+        let inputs = [
+            input(TXID_1, 0, KEY_1, false),
+            input(TXID_2, 0, KEY_2, false),
+        ];
+        let vectors = [
+            (
+                2,
+                LABEL_2_SPEND_KEY,
+                "f371bc2e01413c9eca6903a80be883467972b0c40b929be0a6be708cb5442d57",
+            ),
+            (
+                3,
+                LABEL_3_SPEND_KEY,
+                "281a857b704b6443be841b5b13b8aaff367202b95e43c3167def3d559995149b",
+            ),
+            (
+                1_001_337,
+                "03d85092bbe3468f684ce1d8a2a66ebec96a9e6e09e7110720a5d5faa4aa7880d0",
+                "980fef53d96440908c2a302ad5ae378d81bcc7e9b5789e665b37d53fab6a3814",
+            ),
+        ];
+
+        for (label, spend_pubkey, expected) in vectors {
+            let outputs =
+                derive_silent_payment_outputs(&inputs, &[labeled_recipient(spend_pubkey)]).unwrap();
+            assert_eq!(outputs.len(), 1, "label {label}");
+            assert_eq!(outputs[0].to_string(), expected, "label {label}");
+        }
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_labeled_recipients_share_scan_key_counter() {
+        let inputs = [
+            input(TXID_1, 0, KEY_1, false),
+            input(TXID_2, 0, KEY_2, false),
+        ];
+        let recipients = [
+            recipient(),
+            labeled_recipient(LABEL_2_SPEND_KEY),
+            labeled_recipient(LABEL_3_SPEND_KEY),
+        ];
+        let outputs = derive_silent_payment_outputs(&inputs, &recipients).unwrap();
+        let outputs: Vec<String> = outputs.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            outputs,
+            [
+                "3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1",
+                "9a5325fe7d5e63029301de7bcbb03947e1435df069c6570a74d77990110b02fd",
+                "98433283395b41e34deb278fdc4b5fe8aa7c9b463ed20c6e7c3b76c20bfd2a9a",
+            ]
+        );
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_no_inputs() {
+        let result = derive_silent_payment_outputs(&[], &[recipient()]);
+        assert!(matches!(result, Err(SilentPaymentError::NoInputs)));
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use std::{collections::VecDeque, sync::Mutex};
 
-use crate::dns::DnsResolver;
+use crate::dns::{DnsResolver, VerifiedTxtRecords};
 
 #[derive(Default)]
 pub struct MockDnsResolver {
@@ -32,4 +32,12 @@ impl DnsResolver for MockDnsResolver {
 
         Ok(response)
     }
+
+    async fn verified_txt_lookup(&self, dns_name: String) -> Result<VerifiedTxtRecords> {
+        let records = self.txt_lookup(dns_name).await?;
+        Ok(VerifiedTxtRecords {
+            records,
+            proof: Vec::new(),
+        })
+    }
 }
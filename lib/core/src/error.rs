@@ -1,5 +1,9 @@
 use breez_sdk_common::{
-    error::ServiceConnectivityError, input::ParseError, lnurl::error::LnurlError,
+    error::ServiceConnectivityError,
+    fiat::FiatError,
+    input::{Bip353Error, ParseError},
+    lnurl::error::LnurlError,
+    network::BitcoinNetwork,
 };
 use thiserror::Error;
 
@@ -9,6 +13,15 @@ use crate::BuyBitcoinProvider;
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
 pub enum AcceptPaymentProposedFeesError {}
 
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
+pub enum BuildBip353RecordError {
+    #[error(transparent)]
+    Bip353(#[from] Bip353Error),
+    #[error("Error parsing payment instructions: {0}")]
+    Parse(#[from] ParseError),
+}
+
 #[derive(Debug, Error)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
 pub enum BuyBitcoinError {
@@ -39,7 +52,28 @@ impl From<PrepareBuyBitcoinError> for BuyBitcoinError {
 
 #[derive(Debug, Error)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
-pub enum ConnectError {}
+pub enum ConnectError {
+    #[error("Invalid mnemonic: {0}")]
+    InvalidMnemonic(String),
+    #[error(transparent)]
+    ServiceConnectivity(#[from] ServiceConnectivityError),
+    #[error("General error: {0}")]
+    General(String),
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
+pub enum ConvertFiatToMsatError {
+    #[error(transparent)]
+    Fiat(#[from] FiatError),
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
+pub enum ConvertMsatToFiatError {
+    #[error(transparent)]
+    Fiat(#[from] FiatError),
+}
 
 #[derive(Debug, Error)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
@@ -67,6 +101,13 @@ pub enum FetchPaymentProposedFeesError {}
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
 pub enum FetchRecommendedFeesError {}
 
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
+pub enum FormatFiatError {
+    #[error(transparent)]
+    Fiat(#[from] FiatError),
+}
+
 #[derive(Debug, Error)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
 pub enum GetInfoError {}
@@ -94,6 +135,57 @@ pub enum LnurlAuthError {
     LnurlError(#[from] LnurlError),
 }
 
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
+pub enum LnurlChannelError {
+    #[error("Accepting channels is not supported, the remote node is {node_id}@{address}")]
+    Unsupported { node_id: String, address: String },
+    #[error(transparent)]
+    LnurlError(#[from] LnurlError),
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
+pub enum PayBip21Error {
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+    #[error("LNURL-pay failed: {0}")]
+    LnurlPayFailed(String),
+    #[error(transparent)]
+    PickPaymentMethodError(#[from] PickPaymentMethodError),
+    #[error(transparent)]
+    PrepareSendBitcoinError(#[from] PrepareSendBitcoinError),
+    #[error(transparent)]
+    PrepareSendLightningError(#[from] PrepareSendLightningError),
+    #[error(transparent)]
+    PrepareSendLiquidAddressError(#[from] PrepareSendLiquidAddressError),
+    #[error(transparent)]
+    PrepareSendLnurlPayError(#[from] PrepareSendLnurlPayError),
+    #[error(transparent)]
+    SendBitcoinError(#[from] SendBitcoinError),
+    #[error(transparent)]
+    SendLightningError(#[from] SendLightningError),
+    #[error(transparent)]
+    SendLiquidAddressError(#[from] SendLiquidAddressError),
+    #[error(transparent)]
+    SendLnurlPayError(#[from] SendLnurlPayError),
+}
+
+impl PayBip21Error {
+    /// Whether the payment attempt definitely failed without sending any funds, so another
+    /// payment method can be tried without risking to pay twice. This is the case when preparing
+    /// the payment failed, as nothing is sent before that.
+    pub(crate) fn allows_fallback(&self) -> bool {
+        matches!(
+            self,
+            PayBip21Error::PrepareSendBitcoinError(_)
+                | PayBip21Error::PrepareSendLightningError(_)
+                | PayBip21Error::PrepareSendLiquidAddressError(_)
+                | PayBip21Error::PrepareSendLnurlPayError(_)
+        )
+    }
+}
+
 #[derive(Debug, Error)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
 pub enum ParseAndPickError {
@@ -116,13 +208,25 @@ impl From<PickPaymentMethodError> for ParseAndPickError {
 #[derive(Debug, Error)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
 pub enum PickPaymentMethodError {
+    #[error("Insufficient funds: {required_msat} msat required, {spendable_msat} msat spendable")]
+    InsufficientFunds {
+        required_msat: u64,
+        spendable_msat: u64,
+    },
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
     #[error("Unsupported payment method")]
     Unsupported,
+    #[error("Unsupported asset: {0}")]
+    UnsupportedAsset(String),
 }
 
 #[derive(Debug, Error)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
-pub enum PrepareSendBitcoinError {}
+pub enum PrepareSendBitcoinError {
+    #[error("Invalid network: the address is for {0}")]
+    InvalidNetwork(BitcoinNetwork),
+}
 
 #[derive(Debug, Error)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
@@ -143,11 +247,19 @@ pub enum PrepareSendLightningError {}
 
 #[derive(Debug, Error)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
-pub enum PrepareSendLiquidAddressError {}
+pub enum PrepareSendLiquidAddressError {
+    #[error("Paying Liquid addresses is not supported")]
+    Unsupported,
+}
 
 #[derive(Debug, Error)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
-pub enum PrepareSendLnurlPayError {}
+pub enum PrepareSendLnurlPayError {
+    #[error(transparent)]
+    LnurlError(#[from] LnurlError),
+    #[error(transparent)]
+    PrepareSendLightningError(#[from] PrepareSendLightningError),
+}
 
 #[derive(Debug, Error)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
@@ -179,7 +291,10 @@ pub enum SendLightningError {}
 
 #[derive(Debug, Error)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
-pub enum SendLiquidAddressError {}
+pub enum SendLiquidAddressError {
+    #[error("Paying Liquid addresses is not supported")]
+    Unsupported,
+}
 
 #[derive(Debug, Error)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
@@ -200,6 +315,20 @@ pub enum StopError {
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
 pub enum UnregisterWebhookError {}
 
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
+pub enum VerifyBip353RecordError {
+    #[error(transparent)]
+    Bip353(#[from] Bip353Error),
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
+pub enum VerifyLnurlPaymentError {
+    #[error(transparent)]
+    LnurlError(#[from] LnurlError),
+}
+
 #[derive(Debug, Error)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
 pub enum VerifyMessageError {}
@@ -0,0 +1,38 @@
+use std::{io::ErrorKind, path::PathBuf};
+
+use anyhow::Result;
+use breez_sdk_common::fiat::{FiatRateStore, FiatRates};
+
+const FIAT_RATES_FILE: &str = "fiat_rates.json";
+
+/// Persists the last fetched fiat rates as JSON in the data directory
+pub(crate) struct FileFiatRateStore {
+    path: PathBuf,
+}
+
+impl FileFiatRateStore {
+    pub fn new(data_dir: &str) -> Self {
+        FileFiatRateStore {
+            path: PathBuf::from(data_dir).join(FIAT_RATES_FILE),
+        }
+    }
+}
+
+#[breez_sdk_macros::async_trait]
+impl FiatRateStore for FileFiatRateStore {
+    async fn load_fiat_rates(&self) -> Result<Option<FiatRates>> {
+        match std::fs::read(&self.path) {
+            Ok(data) => Ok(Some(serde_json::from_slice(&data)?)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn save_fiat_rates(&self, rates: &FiatRates) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&self.path, serde_json::to_vec(rates)?)?;
+        Ok(())
+    }
+}
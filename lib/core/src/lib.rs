@@ -1,14 +1,22 @@
 mod buy;
 mod error;
 mod event;
+mod fiat;
 mod lnurl;
 mod model;
 mod sdk;
 
-pub use breez_sdk_common::input::{InputType, ParseError, parse};
+pub use breez_sdk_common::dns::{DnsConfig, DnsUpstream};
+pub use breez_sdk_common::fiat::{FiatError, FiatRates};
+pub use breez_sdk_common::input::{
+    Bip353Error, Bip353Record, InputType, ParseError, ParseTrace, ParseTraceEvent, ParserKind,
+    PaymentScheme, parse, parse_with_trace,
+};
+pub use breez_sdk_common::lnurl::pay::{LnurlPayerDetails, LnurlPaymentVerification};
+pub use breez_sdk_common::lnurl::zap::Zap;
 pub use error::*;
 pub use model::*;
-pub use sdk::{BreezSdk, connect};
+pub use sdk::{BreezSdk, connect, convert_fiat_to_msat, convert_msat_to_fiat, format_fiat_amount};
 
 #[cfg(feature = "uniffi")]
 uniffi::setup_scaffolding!();
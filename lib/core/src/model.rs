@@ -1,10 +1,20 @@
 use breez_sdk_common::{
-    fiat::{FiatCurrency, Rate},
+    breez_server::{PRODUCTION_BREEZSERVER_URL, STAGING_BREEZSERVER_URL},
+    dns::DnsConfig,
+    fiat::{FiatCurrency, FiatRates, Rate},
     input::{
-        BitcoinAddress, Bolt11Invoice, Bolt12Invoice, Bolt12Offer, LiquidAddress, LnurlPayRequest,
-        PaymentMethod, ReceiveRequest, SilentPaymentAddress, SuccessActionProcessed,
+        Bip21, Bip353Record, BitcoinAddress, Bolt11Invoice, Bolt12Invoice, Bolt12Offer,
+        LiquidAddress, LnurlPayRequest, PaymentMethod, PaymentMethodType, ReceiveRequest,
+        SilentPaymentAddress, SuccessActionProcessed,
     },
-    lnurl::{LnurlCallbackStatus, LnurlErrorData, auth::LnurlAuthRequestData},
+    lnurl::{
+        LnurlCallbackStatus, LnurlErrorData,
+        auth::LnurlAuthRequestData,
+        channel::{LnurlChannelAction, LnurlChannelRequestData},
+        pay::{LnurlPayerDetails, LnurlPaymentVerification},
+        zap::Zap,
+    },
+    network::BitcoinNetwork,
 };
 use maybe_sync::{MaybeSend, MaybeSync};
 use serde::{Deserialize, Serialize};
@@ -26,6 +36,26 @@ pub struct AddEventListenerResponse {
     pub listener_id: String,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct BuildBip353RecordRequest {
+    /// The `₿user@domain` address to publish the payment instructions at
+    pub address: String,
+    /// A reusable BOLT12 offer of the wallet
+    pub offer: String,
+    /// An optional on-chain fallback address
+    pub bitcoin_address: Option<String>,
+    /// An optional silent payment fallback address
+    pub silent_payment_address: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct BuildBip353RecordResponse {
+    /// The DNSSEC signed TXT record the domain owner has to publish
+    pub record: Bip353Record,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
 pub enum BitcoinPaymentMethod {
@@ -69,6 +99,44 @@ pub struct Config {
     pub mnemonic: String,
     pub network: Network,
     pub data_dir: String,
+    /// How to rank the payment methods of a BIP-21 URI. Defaults to the order of the payment
+    /// methods supported by the SDK.
+    pub payment_method_preference: Option<PaymentMethodPreference>,
+    /// How DNS lookups of BIP-353 and Lightning addresses are resolved. Defaults to
+    /// [`DnsConfig::default`].
+    pub dns: Option<DnsConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct ConvertFiatToMsatRequest {
+    /// The amount of `currency`, rounded to its fraction size before converting
+    pub amount: f64,
+    pub currency: FiatCurrency,
+    /// The rate of `currency`, as returned by [`crate::BreezSdk::fetch_fiat_rates`]
+    pub rate: Rate,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct ConvertFiatToMsatResponse {
+    pub amount_msat: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct ConvertMsatToFiatRequest {
+    pub amount_msat: u64,
+    pub currency: FiatCurrency,
+    /// The rate of `currency`, as returned by [`crate::BreezSdk::fetch_fiat_rates`]
+    pub rate: Rate,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct ConvertMsatToFiatResponse {
+    /// The amount of `currency`, rounded to its fraction size
+    pub amount: f64,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -85,6 +153,10 @@ pub struct FetchFiatCurrenciesResponse {
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct FetchFiatRatesResponse {
     pub rates: Vec<Rate>,
+    /// The unix timestamp, in seconds, of when the rates were fetched
+    pub fetched_at: u64,
+    /// Whether the server is unreachable and the rates are the last fetched ones
+    pub stale: bool,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -115,9 +187,27 @@ pub struct FetchRecommendedFeesResponse {
     pub minimum_fee: u64,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct FormatFiatRequest {
+    pub amount: f64,
+    pub currency: FiatCurrency,
+    /// The locale to format the amount for, such as `en-US`. Formats the amount the default way
+    /// of the currency when not set.
+    pub locale: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct FormatFiatResponse {
+    pub formatted: String,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct GetInfoResponse {
+    /// The balance available for payments, in millisats
+    pub spendable_balance_msat: u64,
     // TODO
 }
 
@@ -187,6 +277,19 @@ pub struct LnurlAuthResponse {
     pub callback_status: LnurlCallbackStatus,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct LnurlChannelRequest {
+    pub data: LnurlChannelRequestData,
+    pub action: LnurlChannelAction,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct LnurlChannelResponse {
+    pub callback_status: LnurlCallbackStatus,
+}
+
 // TODO: Create easier interface for lnurl pay
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
@@ -224,14 +327,59 @@ pub struct LnurlPaySuccessData {
     pub success_action: Option<SuccessActionProcessed>,
 }
 
-#[derive(Clone, Copy, Debug, Display, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Display, EnumString, Eq, PartialEq, Serialize, Deserialize)]
 #[strum(serialize_all = "lowercase")]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
 pub enum Network {
     Mainnet,
+    Testnet,
+    Signet,
     Regtest,
 }
 
+impl Network {
+    /// The Breez server URL of the network. Only mainnet uses the production server.
+    pub fn breez_server_url(&self) -> &'static str {
+        match self {
+            Network::Mainnet => PRODUCTION_BREEZSERVER_URL,
+            Network::Testnet | Network::Signet | Network::Regtest => STAGING_BREEZSERVER_URL,
+        }
+    }
+}
+
+impl From<Network> for BitcoinNetwork {
+    fn from(network: Network) -> Self {
+        match network {
+            Network::Mainnet => BitcoinNetwork::Bitcoin,
+            Network::Testnet => BitcoinNetwork::Testnet3,
+            Network::Signet => BitcoinNetwork::Signet,
+            Network::Regtest => BitcoinNetwork::Regtest,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct PayBip21Request {
+    pub bip_21: Bip21,
+    /// The amount to pay when the BIP-21 URI has none
+    pub amount_msat: Option<u64>,
+    /// Overrides the payment method preference of the [`Config`]
+    pub preference: Option<PaymentMethodPreference>,
+    /// The balance available for payments, see [`GetInfoResponse::spendable_balance_msat`]
+    pub spendable_balance_msat: u64,
+    /// The fee rate of on-chain payments
+    pub fee_rate_sat_per_kw: Option<u32>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct PayBip21Response {
+    pub payment: Payment,
+    /// The payment method that was paid
+    pub payment_method: PickedPaymentMethod,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct Payment {
@@ -276,6 +424,31 @@ pub enum PaymentType {
     Send = 1,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct PaymentMethodPreference {
+    /// The payment method types to pick from, most preferred first. Types that are not listed
+    /// are never picked.
+    pub order: Vec<PaymentMethodType>,
+    /// When set, on-chain payment methods are preferred for BIP-21 amounts of at least this
+    /// many sats.
+    pub onchain_threshold_sat: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct PickPaymentMethodResponse {
+    pub payment_method: PickedPaymentMethod,
+    /// The other payment methods of the payment request, in order of preference. They can be
+    /// used when paying with the picked payment method fails.
+    pub alternatives: Vec<PickedPaymentMethod>,
+    /// The amount to pay, when fixed by the payment request. Lightning and LNURL payment methods
+    /// are already limited to this amount.
+    pub amount_msat: Option<u64>,
+    /// The description of the payment, taken from the BIP-21 message or label
+    pub description: Option<String>,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
 pub enum PickedPaymentMethod {
@@ -289,7 +462,8 @@ pub enum PickedPaymentMethod {
 #[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
 pub enum PickedInputType {
     LnurlAuth(LnurlAuthRequestData),
-    PaymentMethod(PickedPaymentMethod),
+    LnurlChannel(LnurlChannelRequestData),
+    PaymentMethod(PickPaymentMethodResponse),
     ReceiveRequest(ReceiveRequest),
     Url(String),
 }
@@ -374,12 +548,22 @@ pub struct PrepareSendLnurlPayRequest {
     pub amount_msat: u64,
     /// An optional comment for this payment
     pub comment: Option<String>,
+    /// Makes the payment a nostr zap when set, if the LNURL-pay endpoint supports zaps
+    pub zap: Option<Zap>,
+    /// The payer identity to share when the endpoint asks for it, see LUD-18
+    pub payer_details: Option<LnurlPayerDetails>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct PrepareSendLnurlPayResponse {
     pub req: PrepareSendLnurlPayRequest,
+    /// The invoice returned by the LNURL-pay callback, committing to the metadata or the zap
+    /// request in its description hash
+    pub invoice: String,
+    /// The URL to check the payment was settled with [`crate::BreezSdk::verify_lnurl_payment`],
+    /// when the endpoint supports LUD-21
+    pub verify_url: Option<String>,
     pub fee_msat: u64,
     pub fee_breakdown: FeeBreakdown,
 }
@@ -472,6 +656,8 @@ pub trait SdkEventListener: MaybeSend + MaybeSync {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
 pub enum SdkEvent {
+    /// Emitted when the fiat rates are refreshed from the server
+    FiatRatesUpdated(FiatRates),
     PaymentFailed(Payment),
     PaymentPending(Payment),
     PaymentRefundable(Payment),
@@ -551,6 +737,33 @@ pub struct UnregisterWebhookRequest {}
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct UnregisterWebhookResponse {}
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct VerifyBip353RecordRequest {
+    pub record: Bip353Record,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct VerifyBip353RecordResponse {
+    /// The serialized RFC 9102 DNSSEC proof of the published record
+    pub dnssec_proof: Vec<u8>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct VerifyLnurlPaymentRequest {
+    pub verify_url: String,
+    /// How long to poll for the payment to settle. When not set, the verify URL is checked once.
+    pub timeout_secs: Option<u32>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct VerifyLnurlPaymentResponse {
+    pub verification: LnurlPaymentVerification,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct VerifyMessageRequest {
@@ -1,70 +1,223 @@
 use std::collections::HashMap;
 
+use bip39::Mnemonic;
 use breez_sdk_common::{
-    ensure_sdk,
-    fiat::FiatAPI,
-    input::{Bip21, InputType, PaymentMethod, PaymentMethodType, PaymentScheme},
-    lnurl::auth::perform_lnurl_auth,
-    rest::RestClient,
+    breez_server::BreezServer,
+    dns, ensure_sdk,
+    error::ServiceConnectivityError,
+    fiat::{
+        FIAT_RATES_REFRESH_INTERVAL, FiatAPI, FiatRateCache, FiatRatesStatus, fiat_to_msat,
+        format_fiat, msat_to_fiat,
+    },
+    input::{
+        Bip21, Bolt11Invoice, InputParser, InputType, LnurlPayRequest, ParseError, PaymentMethod,
+        PaymentMethodType, PaymentRequestSource, PaymentScheme, build_bip353_record,
+        verify_bip353_record,
+    },
+    lnurl::{
+        auth::perform_lnurl_auth,
+        channel::{LnurlChannelAction, perform_lnurl_channel},
+        pay::{build_payer_data, poll_payment_verification, request_invoice, verify_payment},
+        zap::{NostrKeySigner, build_zap_request},
+    },
+    rest::{ReqwestRestClient, RestClient},
     utils::Arc,
 };
 use tokio::sync::watch;
-use tracing::info;
+use tracing::{info, warn};
+use web_time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::{
     Config, ConnectRequest, GetInfoResponse, LnurlPaymentRequest, Network, ReceiveMethod,
-    buy::BuyBitcoinApi,
+    buy::{BuyBitcoinApi, BuyBitcoinService},
     error::{
-        AcceptPaymentProposedFeesError, BuyBitcoinError, ConnectError, FetchFiatCurrenciesError,
+        AcceptPaymentProposedFeesError, BuildBip353RecordError, BuyBitcoinError, ConnectError,
+        ConvertFiatToMsatError, ConvertMsatToFiatError, FetchFiatCurrenciesError,
         FetchFiatRatesError, FetchOnchainLimitsError, FetchPaymentProposedFeesError,
-        FetchRecommendedFeesError, GetInfoError, GetPaymentError, InitializeLoggingError,
-        ListPaymentsError, ListRefundablesError, LnurlAuthError, ParseAndPickError,
-        PickPaymentMethodError, PrepareBuyBitcoinError, PrepareReceivePaymentError,
-        PrepareRefundError, PrepareSendBitcoinError, PrepareSendLightningError,
-        PrepareSendLiquidAddressError, PrepareSendLnurlPayError, ReceivePaymentError, RefundError,
-        RegisterWebhookError, SendBitcoinError, SendLightningError, SendLiquidAddressError,
-        SendLnurlPayError, SignMessageError, StopError, UnregisterWebhookError, VerifyMessageError,
+        FetchRecommendedFeesError, FormatFiatError, GetInfoError, GetPaymentError,
+        InitializeLoggingError, ListPaymentsError, ListRefundablesError, LnurlAuthError,
+        LnurlChannelError, ParseAndPickError, PayBip21Error, PickPaymentMethodError,
+        PrepareBuyBitcoinError, PrepareReceivePaymentError, PrepareRefundError,
+        PrepareSendBitcoinError, PrepareSendLightningError, PrepareSendLiquidAddressError,
+        PrepareSendLnurlPayError, ReceivePaymentError, RefundError, RegisterWebhookError,
+        SendBitcoinError, SendLightningError, SendLiquidAddressError, SendLnurlPayError,
+        SignMessageError, StopError, UnregisterWebhookError, VerifyBip353RecordError,
+        VerifyLnurlPaymentError, VerifyMessageError,
     },
     event::EventManager,
+    fiat::FileFiatRateStore,
     lnurl::LnurlAuthSigner,
     model::{
         AcceptPaymentProposedFeesRequest, AcceptPaymentProposedFeesResponse,
-        AddEventListenerResponse, BitcoinPaymentMethod, BuyBitcoinRequest, BuyBitcoinResponse,
+        AddEventListenerResponse, BitcoinPaymentMethod, BuildBip353RecordRequest,
+        BuildBip353RecordResponse, BuyBitcoinRequest, BuyBitcoinResponse, ConvertFiatToMsatRequest,
+        ConvertFiatToMsatResponse, ConvertMsatToFiatRequest, ConvertMsatToFiatResponse,
         FetchFiatCurrenciesResponse, FetchFiatRatesResponse, FetchOnchainLimitsResponse,
         FetchPaymentProposedFeesRequest, FetchPaymentProposedFeesResponse,
-        FetchRecommendedFeesResponse, InitializeLoggingRequest, InitializeLoggingResponse,
-        LightningPaymentMethod, LightningPaymentRequest, ListPaymentsRequest, ListPaymentsResponse,
-        ListRefundablesResponse, LnurlAuthRequest, LnurlAuthResponse, LnurlPaymentMethod, Payment,
+        FetchRecommendedFeesResponse, FormatFiatRequest, FormatFiatResponse,
+        InitializeLoggingRequest, InitializeLoggingResponse, LightningPaymentMethod,
+        LightningPaymentRequest, ListPaymentsRequest, ListPaymentsResponse,
+        ListRefundablesResponse, LnurlAuthRequest, LnurlAuthResponse, LnurlChannelRequest,
+        LnurlChannelResponse, LnurlPayResult, LnurlPaymentMethod, PayBip21Request,
+        PayBip21Response, Payment, PaymentMethodPreference, PickPaymentMethodResponse,
         PickedInputType, PickedPaymentMethod, PrepareBuyBitcoinRequest, PrepareBuyBitcoinResponse,
         PrepareReceivePaymentRequest, PrepareReceivePaymentResponse, PrepareRefundRequest,
         PrepareRefundResponse, PrepareSendBitcoinRequest, PrepareSendBitcoinResponse,
         PrepareSendLightningRequest, PrepareSendLightningResponse, PrepareSendLiquidAddressRequest,
         PrepareSendLiquidAddressResponse, PrepareSendLnurlPayRequest, PrepareSendLnurlPayResponse,
         ReceivePaymentRequest, ReceivePaymentResponse, RefundRequest, RefundResponse,
-        RegisterWebhookRequest, RegisterWebhookResponse, RemoveEventListenerRequest,
+        RegisterWebhookRequest, RegisterWebhookResponse, RemoveEventListenerRequest, SdkEvent,
         SdkEventListener, SendBitcoinRequest, SendBitcoinResponse, SendLightningRequest,
         SendLightningResponse, SendLiquidAddressRequest, SendLiquidAddressResponse,
         SendLnurlPayRequest, SendLnurlPayResponse, SignMessageRequest, SignMessageResponse,
-        UnregisterWebhookRequest, UnregisterWebhookResponse, VerifyMessageRequest,
-        VerifyMessageResponse,
+        UnregisterWebhookRequest, UnregisterWebhookResponse, VerifyBip353RecordRequest,
+        VerifyBip353RecordResponse, VerifyLnurlPaymentRequest, VerifyLnurlPaymentResponse,
+        VerifyMessageRequest, VerifyMessageResponse,
     },
 };
 
+/// Share of a Lightning payment reserved for routing fees, in parts per million
+const LIGHTNING_FEE_RESERVE_PPM: u64 = 5_000;
+/// Routing fee reserve of small Lightning payments
+const LIGHTNING_MIN_FEE_RESERVE_MSAT: u64 = 5_000;
+/// Smallest Lightning payment the service routes
+const LIGHTNING_MIN_PAYMENT_MSAT: u64 = 1_000;
+/// The payment methods picked when no preference is configured, most preferred first. Liquid and
+/// silent payment addresses are parsed, but can't be paid yet.
+const SUPPORTED_PAYMENT_METHODS: [PaymentMethodType; 6] = [
+    PaymentMethodType::Bolt12Offer,
+    PaymentMethodType::Bolt11Invoice,
+    PaymentMethodType::Bolt12Invoice,
+    PaymentMethodType::LightningAddress,
+    PaymentMethodType::LnurlPay,
+    PaymentMethodType::BitcoinAddress,
+];
+
 #[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
 pub struct BreezSdk {
     buy_bitcoin_api: Arc<dyn BuyBitcoinApi>,
     config: Config,
-    event_manager: EventManager,
+    event_manager: Arc<EventManager>,
+    dns_resolver: dns::Resolver,
     fiat_api: Arc<dyn FiatAPI>,
+    /// Serves the rates of [`BreezSdk::fiat_api`], falling back to the persisted ones when offline
+    fiat_rate_cache: Arc<FiatRateCache>,
+    /// Parses inputs for the configured network, resolving names with [`Config::dns`]
+    input_parser: InputParser<ReqwestRestClient, dns::Resolver>,
     lnurl_auth_signer: Arc<LnurlAuthSigner>,
+    /// Signs zap requests when no nostr key is provided, with the NIP-06 key of the seed
+    nostr_signer: Arc<NostrKeySigner>,
     rest_client: Arc<dyn RestClient>,
     shutdown_sender: watch::Sender<()>,
     supported: Vec<PaymentMethodType>,
 }
 
 #[cfg_attr(feature = "uniffi", uniffi::export)]
-pub async fn connect(_req: ConnectRequest) -> Result<BreezSdk, ConnectError> {
-    todo!()
+pub async fn connect(req: ConnectRequest) -> Result<BreezSdk, ConnectError> {
+    let config = req.config;
+    let seed = Mnemonic::parse(&config.mnemonic)
+        .map_err(|e| ConnectError::InvalidMnemonic(e.to_string()))?
+        .to_seed("");
+    let nostr_signer = Arc::new(
+        NostrKeySigner::from_seed(&seed).map_err(|e| ConnectError::General(e.to_string()))?,
+    );
+    let dns_resolver = match &config.dns {
+        Some(dns) => dns::Resolver::with_config(dns)
+            .map_err(|e| ConnectError::General(format!("Invalid DNS config: {e}")))?,
+        None => dns::Resolver::new(),
+    };
+    let rest_client: Arc<dyn RestClient> = Arc::new(new_rest_client(&dns_resolver)?);
+    let input_parser = InputParser::new(dns_resolver.clone(), new_rest_client(&dns_resolver)?)
+        .with_network(config.network.into());
+
+    let breez_server = Arc::new(
+        BreezServer::new(config.network.breez_server_url().to_string(), None)
+            .map_err(|e| ConnectError::General(e.to_string()))?,
+    );
+    let fiat_api: Arc<dyn FiatAPI> = breez_server.clone();
+    let fiat_rate_cache = Arc::new(FiatRateCache::new(
+        fiat_api.clone(),
+        Arc::new(FileFiatRateStore::new(&config.data_dir)),
+        FIAT_RATES_REFRESH_INTERVAL,
+    ));
+
+    let (shutdown_sender, _) = watch::channel(());
+    let sdk = BreezSdk {
+        buy_bitcoin_api: Arc::new(BuyBitcoinService::new(config.clone(), breez_server)),
+        config,
+        event_manager: Arc::new(EventManager::new()),
+        dns_resolver,
+        fiat_api,
+        fiat_rate_cache,
+        input_parser,
+        lnurl_auth_signer: Arc::new(LnurlAuthSigner::new()),
+        nostr_signer,
+        rest_client,
+        shutdown_sender,
+        supported: SUPPORTED_PAYMENT_METHODS.to_vec(),
+    };
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    sdk.start_fiat_rates_refresh();
+    Ok(sdk)
+}
+
+/// Creates a REST client resolving hosts with the configured DNS resolver.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+fn new_rest_client(
+    dns_resolver: &dns::Resolver,
+) -> Result<ReqwestRestClient, ServiceConnectivityError> {
+    ReqwestRestClient::with_dns_resolver(dns_resolver.clone())
+}
+
+/// Creates a REST client. Hosts are resolved by the browser, as it doesn't allow custom DNS
+/// resolution.
+#[cfg(all(target_family = "wasm", target_os = "unknown"))]
+fn new_rest_client(
+    _dns_resolver: &dns::Resolver,
+) -> Result<ReqwestRestClient, ServiceConnectivityError> {
+    ReqwestRestClient::new()
+}
+
+/// Converts an amount of fiat currency to millisatoshi, using a rate from
+/// [`BreezSdk::fetch_fiat_rates`].
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn convert_fiat_to_msat(
+    req: ConvertFiatToMsatRequest,
+) -> Result<ConvertFiatToMsatResponse, ConvertFiatToMsatError> {
+    let ConvertFiatToMsatRequest {
+        amount,
+        currency,
+        rate,
+    } = req;
+    let amount_msat = fiat_to_msat(amount, &currency, &rate)?;
+    Ok(ConvertFiatToMsatResponse { amount_msat })
+}
+
+/// Converts an amount in millisatoshi to fiat currency, using a rate from
+/// [`BreezSdk::fetch_fiat_rates`].
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn convert_msat_to_fiat(
+    req: ConvertMsatToFiatRequest,
+) -> Result<ConvertMsatToFiatResponse, ConvertMsatToFiatError> {
+    let ConvertMsatToFiatRequest {
+        amount_msat,
+        currency,
+        rate,
+    } = req;
+    let amount = msat_to_fiat(amount_msat, &currency, &rate)?;
+    Ok(ConvertMsatToFiatResponse { amount })
+}
+
+/// Formats an amount of fiat currency with its symbol, the same way on every platform.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn format_fiat_amount(req: FormatFiatRequest) -> Result<FormatFiatResponse, FormatFiatError> {
+    let FormatFiatRequest {
+        amount,
+        currency,
+        locale,
+    } = req;
+    let formatted = format_fiat(amount, &currency, locale.as_deref())?;
+    Ok(FormatFiatResponse { formatted })
 }
 
 impl BreezSdk {
@@ -96,6 +249,24 @@ impl BreezSdk {
     //     todo!()
     // }
 
+    /// Builds the DNS TXT record publishing the wallet's payment instructions at a BIP-353
+    /// address, for domain owners to set up a human-readable address. See
+    /// [`BreezSdk::verify_bip353_record`] to check it is published.
+    pub async fn build_bip353_record(
+        &self,
+        req: BuildBip353RecordRequest,
+    ) -> Result<BuildBip353RecordResponse, BuildBip353RecordError> {
+        let record = build_bip353_record(
+            &req.address,
+            &req.offer,
+            req.bitcoin_address.as_deref(),
+            req.silent_payment_address.as_deref(),
+        )?;
+        // Rejects payment instructions for another network
+        self.input_parser.parse(&record.value).await?;
+        Ok(BuildBip353RecordResponse { record })
+    }
+
     pub async fn buy_bitcoin(
         &self,
         req: BuyBitcoinRequest,
@@ -140,8 +311,17 @@ impl BreezSdk {
     }
 
     pub async fn fetch_fiat_rates(&self) -> Result<FetchFiatRatesResponse, FetchFiatRatesError> {
-        let rates = self.fiat_api.fetch_fiat_rates().await?;
-        Ok(FetchFiatRatesResponse { rates })
+        let (rates, status) = self.fiat_rate_cache.rates().await?;
+        if status == FiatRatesStatus::Refreshed {
+            self.event_manager
+                .notify(SdkEvent::FiatRatesUpdated(rates.clone()))
+                .await;
+        }
+        Ok(FetchFiatRatesResponse {
+            rates: rates.rates,
+            fetched_at: rates.fetched_at,
+            stale: status == FiatRatesStatus::Stale,
+        })
     }
 
     pub async fn fetch_onchain_limits(
@@ -195,14 +375,45 @@ impl BreezSdk {
         Ok(LnurlAuthResponse { callback_status })
     }
 
-    /// Parses the input string and picks a payment method based on the supported payment methods.
-    pub async fn parse_and_pick(&self, input: &str) -> Result<PickedInputType, ParseAndPickError> {
-        let input = breez_sdk_common::input::parse(input).await?;
+    /// Answers a LNURL-channel request. Spark wallets have no channels, so accepting returns
+    /// [`LnurlChannelError::Unsupported`] with the remote node info, while declining lets the
+    /// service know the channel won't be opened.
+    pub async fn lnurl_channel(
+        &self,
+        req: LnurlChannelRequest,
+    ) -> Result<LnurlChannelResponse, LnurlChannelError> {
+        if let LnurlChannelAction::Accept { .. } = req.action {
+            return Err(LnurlChannelError::Unsupported {
+                node_id: req.data.node_id,
+                address: req.data.address,
+            });
+        }
+
+        let callback_status =
+            perform_lnurl_channel(self.rest_client.as_ref(), &req.data, None, &req.action).await?;
+        Ok(LnurlChannelResponse { callback_status })
+    }
+
+    /// Parses the input string and picks a payment method based on the supported payment methods,
+    /// see [`BreezSdk::pick_payment_method`]. Payment methods for another network and expired
+    /// invoices are rejected with a [`ParseError`].
+    pub async fn parse_and_pick(
+        &self,
+        input: &str,
+        spendable_balance_msat: u64,
+    ) -> Result<PickedInputType, ParseAndPickError> {
+        let input = self.input_parser.parse(input).await?;
         Ok(match input {
             InputType::LnurlAuth(lnurl_auth) => PickedInputType::LnurlAuth(lnurl_auth),
+            InputType::LnurlChannel(lnurl_channel) => PickedInputType::LnurlChannel(lnurl_channel),
             InputType::PaymentRequest(req) => {
-                let payment_method = self.pick_payment_method(req).await?;
-                PickedInputType::PaymentMethod(payment_method)
+                if let PaymentScheme::PaymentMethod(payment_method) = &req {
+                    ensure_not_expired(payment_method)?;
+                }
+                let picked = self
+                    .pick_payment_method(req, spendable_balance_msat, None)
+                    .await?;
+                PickedInputType::PaymentMethod(picked)
             }
             InputType::ReceiveRequest(receive_request) => {
                 PickedInputType::ReceiveRequest(receive_request)
@@ -211,17 +422,65 @@ impl BreezSdk {
         })
     }
 
+    /// Pays a BIP-21 URI with its preferred payment method. When preparing the payment fails,
+    /// nothing has been sent yet and the next payment method is tried. Any other failure is
+    /// returned, so at most one of the payment methods is paid.
+    pub async fn pay_bip21(&self, req: PayBip21Request) -> Result<PayBip21Response, PayBip21Error> {
+        let picked = self
+            .pick_payment_method(
+                PaymentScheme::Bip21(req.bip_21),
+                req.spendable_balance_msat,
+                req.preference,
+            )
+            .await?;
+        let amount_msat = picked.amount_msat.or(req.amount_msat);
+
+        let payment_methods = std::iter::once(picked.payment_method).chain(picked.alternatives);
+        let fee_rate_sat_per_kw = req.fee_rate_sat_per_kw;
+        let (payment, payment_method) = pay_with_fallback(payment_methods, move |payment_method| {
+            self.pay_payment_method(payment_method, amount_msat, fee_rate_sat_per_kw)
+        })
+        .await?;
+        Ok(PayBip21Response {
+            payment,
+            payment_method,
+        })
+    }
+
     /// Picks a payment method from the given payment request, based on the supported payment methods.
     /// Typically used after parsing a payment request with the general input parser.
+    ///
+    /// The payment methods of a BIP-21 URI are ranked by the given preference, falling back to
+    /// the preference set in the [`Config`]. The remaining payable methods are returned as
+    /// alternatives.
+    ///
+    /// Lightning amounts are bounded by the given spendable balance, see
+    /// [`GetInfoResponse::spendable_balance_msat`], keeping a reserve for routing fees. Payment
+    /// requests the balance cannot cover are rejected with
+    /// [`PickPaymentMethodError::InsufficientFunds`].
     pub async fn pick_payment_method(
         &self,
         payment_request: PaymentScheme,
-    ) -> Result<PickedPaymentMethod, PickPaymentMethodError> {
+        spendable_balance_msat: u64,
+        preference: Option<PaymentMethodPreference>,
+    ) -> Result<PickPaymentMethodResponse, PickPaymentMethodError> {
+        let limits = LightningSendLimits::new(spendable_balance_msat);
         // TODO: Liquid should unpack the magic routing hint for example to send to a liquid address directly.
-        Ok(match payment_request {
-            PaymentScheme::Bip21(bip_21) => expand_bip_21(&bip_21, &self.supported)?,
-            PaymentScheme::PaymentMethod(payment_method) => expand_payment_method(payment_method),
-        })
+        match payment_request {
+            PaymentScheme::Bip21(bip_21) => {
+                let preference = preference
+                    .as_ref()
+                    .or(self.config.payment_method_preference.as_ref());
+                let order = rank_payment_methods(&self.supported, preference, bip_21.amount_sat);
+                expand_bip_21(bip_21, &order, &limits)
+            }
+            PaymentScheme::PaymentMethod(payment_method) => Ok(PickPaymentMethodResponse {
+                payment_method: expand_payment_method(payment_method, None, &limits)?,
+                alternatives: Vec::new(),
+                amount_msat: None,
+                description: None,
+            }),
+        }
     }
 
     pub async fn prepare_buy_bitcoin(
@@ -244,10 +503,24 @@ impl BreezSdk {
         })
     }
 
+    /// Prepares an on-chain payment to a bitcoin or silent payment address.
     pub async fn prepare_send_bitcoin(
         &self,
-        _req: PrepareSendBitcoinRequest,
+        req: PrepareSendBitcoinRequest,
     ) -> Result<PrepareSendBitcoinResponse, PrepareSendBitcoinError> {
+        let network = self.config.network.into();
+        let (is_valid_for_network, address_network) = match &req.method {
+            BitcoinPaymentMethod::BitcoinAddress(address) => {
+                (address.is_valid_for_network(network), address.network)
+            }
+            BitcoinPaymentMethod::SilentPaymentAddress(address) => {
+                (address.is_valid_for_network(network), address.network)
+            }
+        };
+        ensure_sdk!(
+            is_valid_for_network,
+            PrepareSendBitcoinError::InvalidNetwork(address_network)
+        );
         todo!()
     }
     pub async fn prepare_send_lightning(
@@ -256,17 +529,91 @@ impl BreezSdk {
     ) -> Result<PrepareSendLightningResponse, PrepareSendLightningError> {
         todo!()
     }
+    /// Prepares a LNURL-pay payment by requesting the invoice from the callback. When
+    /// [`PrepareSendLnurlPayRequest::zap`] is set, a signed zap request is passed as the `nostr`
+    /// parameter and the invoice must commit to it instead of the metadata. The payer data the
+    /// endpoint asks for is filled in from [`PrepareSendLnurlPayRequest::payer_details`], with
+    /// the LNURL-auth linking key used for `pubkey` and `auth`.
     pub async fn prepare_send_lnurl_pay(
         &self,
-        _req: PrepareSendLnurlPayRequest,
+        req: PrepareSendLnurlPayRequest,
     ) -> Result<PrepareSendLnurlPayResponse, PrepareSendLnurlPayError> {
-        todo!()
+        let pay_request = &req.lnurl_pay.request;
+        let zap_request = match &req.zap {
+            Some(zap) => {
+                let zap_request = match &zap.nostr_secret_key {
+                    Some(secret_key) => {
+                        let signer = NostrKeySigner::from_secret_key(secret_key)?;
+                        build_zap_request(
+                            &signer,
+                            pay_request,
+                            zap,
+                            req.amount_msat,
+                            req.comment.as_deref(),
+                        )
+                        .await?
+                    }
+                    None => {
+                        build_zap_request(
+                            self.nostr_signer.as_ref(),
+                            pay_request,
+                            zap,
+                            req.amount_msat,
+                            req.comment.as_deref(),
+                        )
+                        .await?
+                    }
+                };
+                Some(zap_request)
+            }
+            None => None,
+        };
+
+        let payer_data = build_payer_data(
+            self.lnurl_auth_signer.as_ref(),
+            pay_request,
+            &req.payer_details.clone().unwrap_or_default(),
+        )
+        .await?;
+
+        let invoice = request_invoice(
+            self.rest_client.as_ref(),
+            pay_request,
+            req.amount_msat,
+            req.comment.as_deref(),
+            zap_request.as_deref(),
+            payer_data.as_ref(),
+        )
+        .await?;
+        let prepared = self
+            .prepare_send_lightning(PrepareSendLightningRequest {
+                payment_request: LightningPaymentRequest {
+                    min_amount_msat: req.amount_msat,
+                    max_amount_msat: req.amount_msat,
+                    method: LightningPaymentMethod::Bolt11Invoice(Bolt11Invoice {
+                        bolt11: invoice.pr.clone(),
+                        source: PaymentRequestSource::default(),
+                    }),
+                },
+                amount_msat: req.amount_msat,
+            })
+            .await?;
+
+        Ok(PrepareSendLnurlPayResponse {
+            req,
+            invoice: invoice.pr,
+            verify_url: invoice.verify,
+            fee_msat: prepared.fee_msat,
+            fee_breakdown: prepared.fee_breakdown,
+        })
     }
+    /// Prepares a payment to a Liquid address. Not supported yet, as it requires swapping the
+    /// balance to L-BTC.
     pub async fn prepare_send_liquid_address(
         &self,
         _req: PrepareSendLiquidAddressRequest,
     ) -> Result<PrepareSendLiquidAddressResponse, PrepareSendLiquidAddressError> {
-        todo!()
+        Err(PrepareSendLiquidAddressError::Unsupported)
     }
 
     pub async fn prepare_receive_payment(
@@ -313,6 +660,9 @@ impl BreezSdk {
     //     todo!()
     // }
 
+    /// Sends the prepared on-chain payment. Payjoin endpoints published by the receiver are not
+    /// used yet: the payment is always sent as a regular transaction, which BIP-78 receivers are
+    /// required to accept.
     pub async fn send_bitcoin(
         &self,
         _req: SendBitcoinRequest,
@@ -338,7 +688,7 @@ impl BreezSdk {
         &self,
         _req: SendLiquidAddressRequest,
     ) -> Result<SendLiquidAddressResponse, SendLiquidAddressError> {
-        todo!()
+        Err(SendLiquidAddressError::Unsupported)
     }
 
     /// Sign given message with the private key. Returns a zbase encoded signature.
@@ -378,6 +728,35 @@ impl BreezSdk {
         todo!()
     }
 
+    /// Checks the record is currently published by its domain with a valid DNSSEC proof.
+    pub async fn verify_bip353_record(
+        &self,
+        req: VerifyBip353RecordRequest,
+    ) -> Result<VerifyBip353RecordResponse, VerifyBip353RecordError> {
+        let dnssec_proof = verify_bip353_record(&self.dns_resolver, &req.record).await?;
+        Ok(VerifyBip353RecordResponse { dnssec_proof })
+    }
+
+    /// Checks whether a LNURL-pay payment was settled on the merchant side, using the LUD-21
+    /// verify URL returned by [`BreezSdk::prepare_send_lnurl_pay`].
+    pub async fn verify_lnurl_payment(
+        &self,
+        req: VerifyLnurlPaymentRequest,
+    ) -> Result<VerifyLnurlPaymentResponse, VerifyLnurlPaymentError> {
+        let verification = match req.timeout_secs {
+            Some(timeout_secs) => {
+                poll_payment_verification(
+                    self.rest_client.as_ref(),
+                    &req.verify_url,
+                    Duration::from_secs(timeout_secs.into()),
+                )
+                .await?
+            }
+            None => verify_payment(self.rest_client.as_ref(), &req.verify_url).await?,
+        };
+        Ok(VerifyLnurlPaymentResponse { verification })
+    }
+
     /// Verifies whether given message was signed by the given pubkey and the signature (zbase encoded) is valid.
     pub async fn verify_message(
         &self,
@@ -388,6 +767,105 @@ impl BreezSdk {
 }
 
 impl BreezSdk {
+    /// Refreshes the fiat rates in the background until the SDK is stopped, notifying the
+    /// listeners of the refreshed rates.
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    fn start_fiat_rates_refresh(&self) {
+        let fiat_rate_cache = self.fiat_rate_cache.clone();
+        let event_manager = self.event_manager.clone();
+        let shutdown = self.shutdown_sender.subscribe();
+        tokio::spawn(async move {
+            fiat_rate_cache
+                .refresh_periodically(
+                    |rates| {
+                        let event_manager = event_manager.clone();
+                        async move {
+                            event_manager
+                                .notify(SdkEvent::FiatRatesUpdated(rates))
+                                .await;
+                        }
+                    },
+                    shutdown,
+                )
+                .await;
+        });
+    }
+
+    async fn pay_payment_method(
+        &self,
+        payment_method: PickedPaymentMethod,
+        amount_msat: Option<u64>,
+        fee_rate_sat_per_kw: Option<u32>,
+    ) -> Result<Payment, PayBip21Error> {
+        let missing_amount = || PayBip21Error::InvalidAmount("An amount is required".to_string());
+        Ok(match payment_method {
+            PickedPaymentMethod::Bitcoin(method) => {
+                let prepared = self
+                    .prepare_send_bitcoin(PrepareSendBitcoinRequest {
+                        method,
+                        amount_msat: amount_msat.ok_or_else(missing_amount)?,
+                        fee_rate_sat_per_kw,
+                    })
+                    .await?;
+                self.send_bitcoin(SendBitcoinRequest { prepared })
+                    .await?
+                    .payment
+            }
+            PickedPaymentMethod::Lightning(payment_request) => {
+                let amount_msat =
+                    if payment_request.min_amount_msat == payment_request.max_amount_msat {
+                        payment_request.min_amount_msat
+                    } else {
+                        amount_msat.ok_or_else(missing_amount)?
+                    };
+                let prepared = self
+                    .prepare_send_lightning(PrepareSendLightningRequest {
+                        payment_request,
+                        amount_msat,
+                    })
+                    .await?;
+                self.send_lightning(SendLightningRequest { prepared })
+                    .await?
+                    .payment
+            }
+            PickedPaymentMethod::LiquidAddress(address) => {
+                let prepared = self
+                    .prepare_send_liquid_address(PrepareSendLiquidAddressRequest {
+                        address,
+                        amount_msat: amount_msat.ok_or_else(missing_amount)?,
+                    })
+                    .await?;
+                self.send_liquid_address(SendLiquidAddressRequest { prepared })
+                    .await?
+                    .payment
+            }
+            PickedPaymentMethod::LnurlPay(lnurl_pay) => {
+                let prepared = self
+                    .prepare_send_lnurl_pay(PrepareSendLnurlPayRequest {
+                        lnurl_pay,
+                        amount_msat: amount_msat.ok_or_else(missing_amount)?,
+                        comment: None,
+                        zap: None,
+                        payer_details: None,
+                    })
+                    .await?;
+                match self
+                    .send_lnurl_pay(SendLnurlPayRequest { prepared })
+                    .await?
+                    .result
+                {
+                    LnurlPayResult::EndpointSuccess(data) => data.payment,
+                    LnurlPayResult::EndpointError(data) => {
+                        return Err(PayBip21Error::LnurlPayFailed(data.reason));
+                    }
+                    LnurlPayResult::PayError(data) => {
+                        return Err(PayBip21Error::LnurlPayFailed(data.reason));
+                    }
+                }
+            }
+        })
+    }
+
     fn validate_buy_bitcoin(&self, amount_sat: u64) -> Result<(), PrepareBuyBitcoinError> {
         ensure_sdk!(
             self.config.network == Network::Mainnet,
@@ -404,65 +882,247 @@ impl BreezSdk {
     }
 }
 
-/// Picks a payment method from the given BIP21, based on the supported payment methods.
-fn expand_bip_21(
-    bip_21: &Bip21,
+/// Pays the payment methods in order until one succeeds, returning the payment and the paid
+/// payment method.
+///
+/// Only failures that guarantee no funds were sent fall back to the next payment method, see
+/// [`PayBip21Error::allows_fallback`], so at most one of them can succeed.
+async fn pay_with_fallback<T, F, Fut>(
+    payment_methods: impl IntoIterator<Item = PickedPaymentMethod>,
+    mut pay: F,
+) -> Result<(T, PickedPaymentMethod), PayBip21Error>
+where
+    F: FnMut(PickedPaymentMethod) -> Fut,
+    Fut: Future<Output = Result<T, PayBip21Error>>,
+{
+    let mut last_err = None;
+    for payment_method in payment_methods {
+        match pay(payment_method.clone()).await {
+            Ok(payment) => return Ok((payment, payment_method)),
+            Err(err) if err.allows_fallback() => {
+                warn!("Paying BIP-21 payment method failed, trying the next one: {err}");
+                last_err = Some(err);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    Err(last_err.unwrap_or(PayBip21Error::PickPaymentMethodError(
+        PickPaymentMethodError::Unsupported,
+    )))
+}
+
+/// The amounts that can be sent over Lightning, keeping a reserve for routing fees.
+struct LightningSendLimits {
+    spendable_msat: u64,
+    min_amount_msat: u64,
+    max_amount_msat: u64,
+}
+
+impl LightningSendLimits {
+    fn new(spendable_msat: u64) -> Self {
+        // The largest amount that leaves room for the proportional reserve.
+        let max_proportional_msat = u128::from(spendable_msat)
+            .saturating_mul(1_000_000)
+            .checked_div(u128::from(
+                LIGHTNING_FEE_RESERVE_PPM.saturating_add(1_000_000),
+            ))
+            .and_then(|amount| u64::try_from(amount).ok())
+            .unwrap_or_default();
+        Self {
+            spendable_msat,
+            min_amount_msat: LIGHTNING_MIN_PAYMENT_MSAT,
+            max_amount_msat: spendable_msat
+                .saturating_sub(LIGHTNING_MIN_FEE_RESERVE_MSAT)
+                .min(max_proportional_msat),
+        }
+    }
+
+    /// Checks the balance covers paying the amount, including the fee reserve.
+    fn check_amount(&self, amount_msat: u64) -> Result<(), PickPaymentMethodError> {
+        let fee_reserve_msat = amount_msat
+            .saturating_mul(LIGHTNING_FEE_RESERVE_PPM)
+            .div_ceil(1_000_000)
+            .max(LIGHTNING_MIN_FEE_RESERVE_MSAT);
+        let required_msat = amount_msat.saturating_add(fee_reserve_msat);
+        ensure_sdk!(
+            required_msat <= self.spendable_msat,
+            PickPaymentMethodError::InsufficientFunds {
+                required_msat,
+                spendable_msat: self.spendable_msat,
+            }
+        );
+        Ok(())
+    }
+
+    /// Returns the payment request for the given amount, or the payable range when the payment
+    /// request has no amount.
+    fn payment_request(
+        &self,
+        amount_msat: Option<u64>,
+        method: LightningPaymentMethod,
+    ) -> Result<LightningPaymentRequest, PickPaymentMethodError> {
+        let (min_amount_msat, max_amount_msat) = match amount_msat {
+            Some(amount_msat) => (amount_msat, amount_msat),
+            None => (self.min_amount_msat, self.max_amount_msat),
+        };
+        self.check_amount(min_amount_msat)?;
+        Ok(LightningPaymentRequest {
+            min_amount_msat,
+            max_amount_msat,
+            method,
+        })
+    }
+}
+
+/// Orders the supported payment method types by the preference.
+fn rank_payment_methods(
     supported: &[PaymentMethodType],
-) -> Result<PickedPaymentMethod, PickPaymentMethodError> {
+    preference: Option<&PaymentMethodPreference>,
+    amount_sat: Option<u64>,
+) -> Vec<PaymentMethodType> {
+    let Some(preference) = preference else {
+        return supported.to_vec();
+    };
+
+    let mut order: Vec<PaymentMethodType> = preference
+        .order
+        .iter()
+        .filter(|payment_method_type| supported.contains(payment_method_type))
+        .copied()
+        .collect();
+    if let (Some(threshold_sat), Some(amount_sat)) = (preference.onchain_threshold_sat, amount_sat)
+        && amount_sat >= threshold_sat
+    {
+        // Stable sort, keeping the preferred order within on-chain and off-chain methods.
+        order.sort_by_key(|payment_method_type| {
+            !matches!(
+                payment_method_type,
+                PaymentMethodType::BitcoinAddress | PaymentMethodType::SilentPaymentAddress
+            )
+        });
+    }
+    order
+}
+
+/// Picks a payment method from the given BIP21, in the given order of payment method types.
+///
+/// Payment methods the balance cannot cover, that do not accept the BIP-21 amount, or that request
+/// an asset other than L-BTC, are skipped in favor of the next one.
+fn expand_bip_21(
+    bip_21: Bip21,
+    order: &[PaymentMethodType],
+    limits: &LightningSendLimits,
+) -> Result<PickPaymentMethodResponse, PickPaymentMethodError> {
+    let amount_msat = bip_21
+        .amount_sat
+        .map(|amount_sat| amount_sat.saturating_mul(1_000));
     let mut payment_methods = HashMap::new();
-    for payment_method in &bip_21.payment_methods {
+    for payment_method in bip_21.payment_methods {
         payment_methods
             .entry(payment_method.get_type())
-            .or_insert_with(|| payment_method.clone());
+            .or_insert(payment_method);
     }
 
-    for supported_method in supported {
-        let Some(payment_method) = payment_methods.remove(supported_method) else {
+    let mut picked = Vec::new();
+    let mut last_error = None;
+    for payment_method_type in order {
+        let Some(payment_method) = payment_methods.remove(payment_method_type) else {
             continue;
         };
 
-        return Ok(expand_payment_method(payment_method));
+        match expand_payment_method(payment_method, amount_msat, limits) {
+            Ok(payment_method) => picked.push(payment_method),
+            // Another payment method in the URI may still be payable.
+            Err(
+                err @ (PickPaymentMethodError::InsufficientFunds { .. }
+                | PickPaymentMethodError::InvalidAmount(_)
+                | PickPaymentMethodError::UnsupportedAsset(_)),
+            ) => {
+                last_error = Some(err);
+            }
+            Err(err) => return Err(err),
+        }
     }
 
-    Err(PickPaymentMethodError::Unsupported)
+    let mut picked = picked.into_iter();
+    let Some(payment_method) = picked.next() else {
+        return Err(last_error.unwrap_or(PickPaymentMethodError::Unsupported));
+    };
+    Ok(PickPaymentMethodResponse {
+        payment_method,
+        alternatives: picked.collect(),
+        amount_msat,
+        description: bip_21.message.or(bip_21.label),
+    })
+}
+
+/// Validates that the parsed invoice or offer has not expired.
+fn ensure_not_expired(payment_method: &PaymentMethod) -> Result<(), ParseError> {
+    let expired_at = match payment_method {
+        PaymentMethod::Bolt11Invoice(invoice) => invoice.timestamp.checked_add(invoice.expiry),
+        PaymentMethod::Bolt12Offer(offer) => offer.absolute_expiry,
+        _ => None,
+    };
+    if let Some(expired_at) = expired_at {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or_default();
+        ensure_sdk!(expired_at > now, ParseError::InvoiceExpired { expired_at });
+    }
+    Ok(())
 }
 
-fn expand_payment_method(payment_method: PaymentMethod) -> PickedPaymentMethod {
-    match payment_method {
+/// Expands the payment method into a payable request. When an amount is given, the request is
+/// limited to it.
+fn expand_payment_method(
+    payment_method: PaymentMethod,
+    amount_msat: Option<u64>,
+    limits: &LightningSendLimits,
+) -> Result<PickedPaymentMethod, PickPaymentMethodError> {
+    Ok(match payment_method {
         PaymentMethod::BitcoinAddress(bitcoin_address) => {
             PickedPaymentMethod::Bitcoin(BitcoinPaymentMethod::BitcoinAddress(bitcoin_address))
         }
         PaymentMethod::Bolt11Invoice(bolt11_invoice) => {
-            PickedPaymentMethod::Lightning(LightningPaymentRequest {
-                max_amount_msat: bolt11_invoice.amount_msat.unwrap_or(u64::MAX), // TODO: Set max amount to sane value.
-                min_amount_msat: bolt11_invoice.amount_msat.unwrap_or(0), // TODO: Set min amount to minimum payable amount.
-                method: LightningPaymentMethod::Bolt11Invoice(bolt11_invoice.invoice),
-            })
+            PickedPaymentMethod::Lightning(limits.payment_request(
+                bolt11_invoice.amount_msat.or(amount_msat),
+                LightningPaymentMethod::Bolt11Invoice(bolt11_invoice.invoice),
+            )?)
         }
         PaymentMethod::Bolt12Invoice(bolt12_invoice) => {
-            PickedPaymentMethod::Lightning(LightningPaymentRequest {
-                max_amount_msat: bolt12_invoice.amount_msat,
-                min_amount_msat: bolt12_invoice.amount_msat,
-                method: LightningPaymentMethod::Bolt12Invoice(bolt12_invoice.invoice),
-            })
+            PickedPaymentMethod::Lightning(limits.payment_request(
+                Some(bolt12_invoice.amount_msat),
+                LightningPaymentMethod::Bolt12Invoice(bolt12_invoice.invoice),
+            )?)
         }
         PaymentMethod::Bolt12Offer(bolt12_offer) => {
-            PickedPaymentMethod::Lightning(LightningPaymentRequest {
-                max_amount_msat: u64::MAX, // TODO: Set max amount to sane value.
-                min_amount_msat: 0,        // TODO: Set min amount to minimum payable amount.
-                method: LightningPaymentMethod::Bolt12Offer(bolt12_offer.offer),
-            })
+            PickedPaymentMethod::Lightning(limits.payment_request(
+                amount_msat,
+                LightningPaymentMethod::Bolt12Offer(bolt12_offer.offer),
+            )?)
         }
         PaymentMethod::LightningAddress(lightning_address) => {
+            let pay_request = limit_lnurl_pay_request(lightning_address.pay_request, amount_msat)?;
+            limits.check_amount(pay_request.min_sendable)?;
             PickedPaymentMethod::LnurlPay(LnurlPaymentRequest {
-                request: lightning_address.pay_request,
+                request: pay_request,
                 payment_method: LnurlPaymentMethod::LightningAddress(lightning_address.address),
             })
         }
         PaymentMethod::LiquidAddress(liquid_address) => {
+            // Only L-BTC can be sent.
+            if !liquid_address.is_lbtc() {
+                return Err(PickPaymentMethodError::UnsupportedAsset(
+                    liquid_address.asset_id.unwrap_or_default(),
+                ));
+            }
             PickedPaymentMethod::LiquidAddress(liquid_address)
         }
         PaymentMethod::LnurlPay(lnurl_pay_request) => {
+            let lnurl_pay_request = limit_lnurl_pay_request(lnurl_pay_request, amount_msat)?;
+            limits.check_amount(lnurl_pay_request.min_sendable)?;
             let url = lnurl_pay_request.url.clone();
             PickedPaymentMethod::LnurlPay(LnurlPaymentRequest {
                 request: lnurl_pay_request,
@@ -474,5 +1134,412 @@ fn expand_payment_method(payment_method: PaymentMethod) -> PickedPaymentMethod {
                 silent_payment_address,
             ))
         }
+    })
+}
+
+/// Limits the sendable range of the LNURL-pay request to the given amount.
+fn limit_lnurl_pay_request(
+    mut pay_request: LnurlPayRequest,
+    amount_msat: Option<u64>,
+) -> Result<LnurlPayRequest, PickPaymentMethodError> {
+    let Some(amount_msat) = amount_msat else {
+        return Ok(pay_request);
+    };
+    ensure_sdk!(
+        (pay_request.min_sendable..=pay_request.max_sendable).contains(&amount_msat),
+        PickPaymentMethodError::InvalidAmount(format!(
+            "{amount_msat} msat is outside of the sendable range {}-{} msat",
+            pay_request.min_sendable, pay_request.max_sendable
+        ))
+    );
+    pay_request.min_sendable = amount_msat;
+    pay_request.max_sendable = amount_msat;
+    Ok(pay_request)
+}
+
+#[cfg(test)]
+mod tests {
+    use breez_sdk_common::{
+        input::{
+            Bip21, BitcoinAddress, Bolt11Invoice, DetailedBolt11Invoice, LiquidAddress,
+            PaymentMethod, PaymentMethodType, PaymentRequestSource,
+        },
+        network::BitcoinNetwork,
+    };
+
+    use super::{LightningSendLimits, expand_bip_21, pay_with_fallback, rank_payment_methods};
+    use crate::{
+        error::{PayBip21Error, PickPaymentMethodError, PrepareSendBitcoinError},
+        model::{
+            BitcoinPaymentMethod, LightningPaymentMethod, LightningPaymentRequest,
+            PaymentMethodPreference, PickedPaymentMethod,
+        },
+    };
+
+    const SUPPORTED: [PaymentMethodType; 3] = [
+        PaymentMethodType::Bolt11Invoice,
+        PaymentMethodType::LiquidAddress,
+        PaymentMethodType::BitcoinAddress,
+    ];
+
+    fn bitcoin_address() -> PaymentMethod {
+        PaymentMethod::BitcoinAddress(BitcoinAddress {
+            address: "1andreas3batLhQa2FawWjeyjCqyBzypd".to_string(),
+            network: BitcoinNetwork::Bitcoin,
+            payjoin: None,
+            source: PaymentRequestSource::default(),
+        })
+    }
+
+    fn bolt11_invoice(amount_msat: Option<u64>) -> PaymentMethod {
+        PaymentMethod::Bolt11Invoice(DetailedBolt11Invoice {
+            amount_msat,
+            description: None,
+            description_hash: None,
+            expiry: 3600,
+            invoice: Bolt11Invoice {
+                bolt11: "lnbc1".to_string(),
+                source: PaymentRequestSource::default(),
+            },
+            min_final_cltv_expiry_delta: 18,
+            network: BitcoinNetwork::Bitcoin,
+            payee_pubkey: String::new(),
+            payment_hash: String::new(),
+            payment_secret: String::new(),
+            routing_hints: Vec::new(),
+            timestamp: 0,
+        })
+    }
+
+    fn liquid_address(asset_id: &str) -> PaymentMethod {
+        PaymentMethod::LiquidAddress(LiquidAddress {
+            address:
+                "VJLCbLBTCdxhWyjVLdjcSmGAksVMtabYg15maSi93zknQD2ihC38R7CUd8KbDFnV8A4hiykxnRB3Uv6d"
+                    .to_string(),
+            asset_id: Some(asset_id.to_string()),
+            network: BitcoinNetwork::Bitcoin,
+            source: PaymentRequestSource::default(),
+        })
+    }
+
+    fn picked_lightning(amount_msat: u64) -> PickedPaymentMethod {
+        PickedPaymentMethod::Lightning(LightningPaymentRequest {
+            min_amount_msat: amount_msat,
+            max_amount_msat: amount_msat,
+            method: LightningPaymentMethod::Bolt11Invoice(Bolt11Invoice {
+                bolt11: "lnbc1".to_string(),
+                source: PaymentRequestSource::default(),
+            }),
+        })
+    }
+
+    fn picked_amount_msat(payment_method: &PickedPaymentMethod) -> u64 {
+        let PickedPaymentMethod::Lightning(request) = payment_method else {
+            panic!("Expected a Lightning payment method");
+        };
+        request.min_amount_msat
+    }
+
+    /// An error of a payment attempt that did not send any funds.
+    fn retryable_error(network: BitcoinNetwork) -> PayBip21Error {
+        PayBip21Error::PrepareSendBitcoinError(PrepareSendBitcoinError::InvalidNetwork(network))
+    }
+
+    fn bip_21_uri(payment_methods: Vec<PaymentMethod>) -> Bip21 {
+        Bip21 {
+            payment_methods,
+            ..Default::default()
+        }
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_lightning_send_limits_min_fee_reserve() {
+        let limits = LightningSendLimits::new(1_000_000);
+        assert_eq!(limits.min_amount_msat, 1_000);
+        assert_eq!(limits.max_amount_msat, 995_000);
+        assert!(limits.check_amount(995_000).is_ok());
+        assert!(matches!(
+            limits.check_amount(995_001),
+            Err(PickPaymentMethodError::InsufficientFunds {
+                required_msat: 1_000_001,
+                spendable_msat: 1_000_000,
+            })
+        ));
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_lightning_send_limits_proportional_fee_reserve() {
+        let limits = LightningSendLimits::new(10_000_000_000);
+        assert_eq!(limits.max_amount_msat, 9_950_248_756);
+        assert!(limits.check_amount(9_950_248_756).is_ok());
+        assert!(matches!(
+            limits.check_amount(9_950_248_757),
+            Err(PickPaymentMethodError::InsufficientFunds {
+                required_msat: 10_000_000_001,
+                ..
+            })
+        ));
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_lightning_send_limits_payment_request() {
+        let limits = LightningSendLimits::new(1_000_000);
+        let method = LightningPaymentMethod::Bolt11Invoice(Bolt11Invoice {
+            bolt11: "lnbc1".to_string(),
+            source: PaymentRequestSource::default(),
+        });
+
+        let request = limits.payment_request(None, method.clone()).unwrap();
+        assert_eq!(request.min_amount_msat, 1_000);
+        assert_eq!(request.max_amount_msat, 995_000);
+
+        let request = limits
+            .payment_request(Some(500_000), method.clone())
+            .unwrap();
+        assert_eq!(request.min_amount_msat, 500_000);
+        assert_eq!(request.max_amount_msat, 500_000);
+
+        assert!(matches!(
+            limits.payment_request(Some(1_000_000), method.clone()),
+            Err(PickPaymentMethodError::InsufficientFunds { .. })
+        ));
+        assert!(matches!(
+            LightningSendLimits::new(0).payment_request(None, method),
+            Err(PickPaymentMethodError::InsufficientFunds {
+                required_msat: 6_000,
+                spendable_msat: 0,
+            })
+        ));
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_expand_bip_21_picks_first_supported() {
+        let bip_21 = bip_21_uri(vec![bitcoin_address(), bolt11_invoice(Some(100_000))]);
+        let limits = LightningSendLimits::new(1_000_000);
+
+        let picked = expand_bip_21(bip_21.clone(), &SUPPORTED, &limits).unwrap();
+        assert!(matches!(
+            picked.payment_method,
+            PickedPaymentMethod::Lightning(request)
+            if request.min_amount_msat == 100_000 && request.max_amount_msat == 100_000
+        ));
+        assert!(matches!(
+            &picked.alternatives[..],
+            [PickedPaymentMethod::Bitcoin(
+                BitcoinPaymentMethod::BitcoinAddress(_)
+            )]
+        ));
+
+        let picked = expand_bip_21(
+            bip_21.clone(),
+            &[PaymentMethodType::BitcoinAddress],
+            &limits,
+        )
+        .unwrap();
+        assert!(matches!(
+            picked.payment_method,
+            PickedPaymentMethod::Bitcoin(BitcoinPaymentMethod::BitcoinAddress(_))
+        ));
+        assert!(picked.alternatives.is_empty());
+
+        let result = expand_bip_21(bip_21, &[PaymentMethodType::Bolt12Offer], &limits);
+        assert!(matches!(result, Err(PickPaymentMethodError::Unsupported)));
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_expand_bip_21_amount() {
+        let bip_21 = Bip21 {
+            amount_sat: Some(100),
+            label: Some("label".to_string()),
+            message: Some("message".to_string()),
+            ..bip_21_uri(vec![bolt11_invoice(None), bitcoin_address()])
+        };
+        let limits = LightningSendLimits::new(1_000_000);
+
+        let picked = expand_bip_21(bip_21, &SUPPORTED, &limits).unwrap();
+        assert!(matches!(
+            picked.payment_method,
+            PickedPaymentMethod::Lightning(request)
+            if request.min_amount_msat == 100_000 && request.max_amount_msat == 100_000
+        ));
+        assert_eq!(picked.amount_msat, Some(100_000));
+        assert_eq!(picked.description.as_deref(), Some("message"));
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_expand_bip_21_skips_unpayable_methods() {
+        let limits = LightningSendLimits::new(1_000_000);
+        let usdt = "ce091c998b83c78bb71a632313ba3760f1763d9cfcffae02258ffa9865a37bd2";
+
+        // The invoice exceeds the balance and the Liquid address requests another asset.
+        let bip_21 = bip_21_uri(vec![
+            bolt11_invoice(Some(2_000_000)),
+            liquid_address(usdt),
+            bitcoin_address(),
+        ]);
+        let picked = expand_bip_21(bip_21, &SUPPORTED, &limits).unwrap();
+        assert!(matches!(
+            picked.payment_method,
+            PickedPaymentMethod::Bitcoin(BitcoinPaymentMethod::BitcoinAddress(_))
+        ));
+        assert!(picked.alternatives.is_empty());
+
+        // Without an alternative, the reason of the last skipped method is returned.
+        let bip_21 = bip_21_uri(vec![bolt11_invoice(Some(2_000_000))]);
+        let result = expand_bip_21(bip_21, &SUPPORTED, &limits);
+        assert!(matches!(
+            result,
+            Err(PickPaymentMethodError::InsufficientFunds { .. })
+        ));
+
+        let bip_21 = bip_21_uri(vec![liquid_address(usdt)]);
+        let result = expand_bip_21(bip_21, &SUPPORTED, &limits);
+        assert!(matches!(
+            result,
+            Err(PickPaymentMethodError::UnsupportedAsset(asset_id)) if asset_id == usdt
+        ));
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_rank_payment_methods() {
+        // Without preference the supported order is kept.
+        assert_eq!(
+            rank_payment_methods(&SUPPORTED, None, Some(1_000)),
+            SUPPORTED
+        );
+
+        // Preferred types are filtered by the supported ones.
+        let preference = PaymentMethodPreference {
+            order: vec![
+                PaymentMethodType::BitcoinAddress,
+                PaymentMethodType::Bolt12Offer,
+                PaymentMethodType::Bolt11Invoice,
+            ],
+            onchain_threshold_sat: None,
+        };
+        assert_eq!(
+            rank_payment_methods(&SUPPORTED, Some(&preference), Some(1_000)),
+            [
+                PaymentMethodType::BitcoinAddress,
+                PaymentMethodType::Bolt11Invoice
+            ]
+        );
+    }
+
+    #[breez_sdk_macros::test_all]
+    fn test_rank_payment_methods_onchain_threshold() {
+        let preference = PaymentMethodPreference {
+            order: vec![
+                PaymentMethodType::Bolt11Invoice,
+                PaymentMethodType::LiquidAddress,
+                PaymentMethodType::SilentPaymentAddress,
+                PaymentMethodType::BitcoinAddress,
+            ],
+            onchain_threshold_sat: Some(100_000),
+        };
+        let supported = [
+            PaymentMethodType::BitcoinAddress,
+            PaymentMethodType::Bolt11Invoice,
+            PaymentMethodType::LiquidAddress,
+            PaymentMethodType::SilentPaymentAddress,
+        ];
+
+        // Below the threshold, or without amount, the preferred order is kept.
+        for amount_sat in [Some(99_999), None] {
+            assert_eq!(
+                rank_payment_methods(&supported, Some(&preference), amount_sat),
+                preference.order
+            );
+        }
+
+        // From the threshold on, on-chain methods move to the front in their preferred order.
+        assert_eq!(
+            rank_payment_methods(&supported, Some(&preference), Some(100_000)),
+            [
+                PaymentMethodType::SilentPaymentAddress,
+                PaymentMethodType::BitcoinAddress,
+                PaymentMethodType::Bolt11Invoice,
+                PaymentMethodType::LiquidAddress,
+            ]
+        );
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_pay_with_fallback_on_retryable_error() {
+        let mut attempts = Vec::new();
+        let payment_methods = [
+            picked_lightning(1_000),
+            picked_lightning(2_000),
+            picked_lightning(3_000),
+        ];
+        let result = pay_with_fallback(payment_methods, |payment_method| {
+            let amount_msat = picked_amount_msat(&payment_method);
+            attempts.push(amount_msat);
+            let result = match amount_msat {
+                1_000 => Err(retryable_error(BitcoinNetwork::Testnet3)),
+                amount_msat => Ok(amount_msat),
+            };
+            async move { result }
+        })
+        .await;
+
+        let (paid_msat, payment_method) = result.unwrap();
+        assert_eq!(paid_msat, 2_000);
+        assert_eq!(picked_amount_msat(&payment_method), 2_000);
+        assert_eq!(attempts, [1_000, 2_000]);
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_pay_with_fallback_stops_on_terminal_error() {
+        let mut attempts = Vec::new();
+        let payment_methods = [picked_lightning(1_000), picked_lightning(2_000)];
+        let result = pay_with_fallback(payment_methods, |payment_method| {
+            let amount_msat = picked_amount_msat(&payment_method);
+            attempts.push(amount_msat);
+            let result = match amount_msat {
+                1_000 => Err(PayBip21Error::LnurlPayFailed("Route not found".to_string())),
+                amount_msat => Ok(amount_msat),
+            };
+            async move { result }
+        })
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(PayBip21Error::LnurlPayFailed(reason)) if reason == "Route not found"
+        ));
+        assert_eq!(attempts, [1_000]);
+    }
+
+    #[breez_sdk_macros::async_test_all]
+    async fn test_pay_with_fallback_returns_last_error() {
+        let mut attempts = Vec::new();
+        let payment_methods = [picked_lightning(1_000), picked_lightning(2_000)];
+        let result = pay_with_fallback(payment_methods, |payment_method| {
+            let amount_msat = picked_amount_msat(&payment_method);
+            attempts.push(amount_msat);
+            let network = match amount_msat {
+                1_000 => BitcoinNetwork::Testnet3,
+                _ => BitcoinNetwork::Regtest,
+            };
+            async move { Err::<(), _>(retryable_error(network)) }
+        })
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(PayBip21Error::PrepareSendBitcoinError(
+                PrepareSendBitcoinError::InvalidNetwork(BitcoinNetwork::Regtest)
+            ))
+        ));
+        assert_eq!(attempts, [1_000, 2_000]);
+
+        let result = pay_with_fallback(Vec::new(), |_| async { Ok(()) }).await;
+        assert!(matches!(
+            result,
+            Err(PayBip21Error::PickPaymentMethodError(
+                PickPaymentMethodError::Unsupported
+            ))
+        ));
     }
 }
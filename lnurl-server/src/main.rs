@@ -0,0 +1,58 @@
+//! A reference LNURL-pay server for local development, serving Lightning addresses registered by
+//! Breez SDK wallets. Registrations are kept in memory.
+//!
+//! Payers resolve `username@domain` at `/.well-known/lnurlp/<username>`. Their invoice requests
+//! are forwarded to the webhook of the wallet, which answers with an invoice created by
+//! `BreezSdk::receive_lightning_address_payment`.
+
+mod server;
+
+use std::{net::SocketAddr, sync::Arc};
+
+use anyhow::Result;
+use clap::Parser;
+use hyper::{server::conn::http1, service::service_fn};
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpListener;
+use tracing::{error, info};
+
+use crate::server::LnurlServer;
+
+#[derive(Parser)]
+#[command(version, about = "Reference LNURL-pay server for Breez SDK Lightning addresses", long_about = None)]
+struct Args {
+    /// Socket address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+
+    /// URL the server is publicly reached at. Its host is the domain of the Lightning addresses.
+    #[arg(long, default_value = "http://localhost:8080")]
+    base_url: String,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let args = Args::parse();
+    let server = Arc::new(LnurlServer::new(&args.base_url)?);
+    let listener = TcpListener::bind(args.listen).await?;
+    info!("Listening on {}", args.listen);
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let server = server.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |req| {
+                let server = server.clone();
+                async move { server.handle(req).await }
+            });
+            if let Err(e) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                error!("Failed to serve connection: {e}");
+            }
+        });
+    }
+}
@@ -0,0 +1,320 @@
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    str::FromStr,
+    sync::{Mutex, PoisonError},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Result, anyhow};
+use bech32::{Bech32, Hrp};
+use breez_sdk_common::{
+    lnurl::{
+        LnurlCallbackStatus, LnurlErrorData,
+        lightning_address::{
+            LnurlPayInvoiceRequest, LnurlPayInvoiceResponse, RegisterLnurlPayRequest,
+            RegisterLnurlPayResponse, UnregisterLnurlPayRequest, lnurl_pay_metadata,
+            register_message, unregister_message, validate_username,
+        },
+    },
+    rest::{ReqwestRestClient, RestClient, parse_json},
+};
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    Method, Request, Response, StatusCode,
+    body::{Bytes, Incoming},
+    header::{CONTENT_TYPE, HeaderValue},
+};
+use lightning::{
+    bitcoin::{
+        hashes::{Hash, sha256},
+        secp256k1::PublicKey,
+    },
+    bolt11_invoice::{Bolt11Invoice, Bolt11InvoiceDescriptionRef},
+    util::message_signing,
+};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::json;
+use tracing::{debug, info};
+
+const MIN_SENDABLE_MSAT: u64 = 1_000;
+const MAX_SENDABLE_MSAT: u64 = 4_000_000_000;
+/// How far the time of a signed request may be from the clock of the server
+const MAX_TIME_DRIFT_SECS: u64 = 300;
+
+enum ApiError {
+    BadRequest(String),
+    Unauthorized,
+    NotFound,
+    Conflict,
+    Webhook(String),
+}
+
+impl ApiError {
+    fn into_response(self) -> Response<Full<Bytes>> {
+        let (status, reason) = match self {
+            ApiError::BadRequest(reason) => (StatusCode::BAD_REQUEST, reason),
+            ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "invalid signature".to_string()),
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "not found".to_string()),
+            ApiError::Conflict => (StatusCode::CONFLICT, "username is taken".to_string()),
+            ApiError::Webhook(reason) => (StatusCode::BAD_GATEWAY, reason),
+        };
+        json_response(
+            status,
+            &LnurlCallbackStatus::ErrorStatus {
+                data: LnurlErrorData { reason },
+            },
+        )
+    }
+}
+
+struct Registration {
+    pubkey: String,
+    webhook_url: String,
+}
+
+pub struct LnurlServer {
+    base_url: String,
+    domain: String,
+    rest_client: ReqwestRestClient,
+    /// Registrations by username
+    registrations: Mutex<HashMap<String, Registration>>,
+}
+
+impl LnurlServer {
+    pub fn new(base_url: &str) -> Result<Self> {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        let domain = base_url
+            .split_once("://")
+            .and_then(|(_, rest)| rest.split('/').next())
+            .filter(|domain| !domain.is_empty())
+            .ok_or_else(|| anyhow!("Invalid base URL: {base_url}"))?
+            .to_string();
+        Ok(Self {
+            base_url,
+            domain,
+            rest_client: ReqwestRestClient::new()?,
+            registrations: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub async fn handle(
+        &self,
+        req: Request<Incoming>,
+    ) -> Result<Response<Full<Bytes>>, Infallible> {
+        let method = req.method().clone();
+        let path = req.uri().path().trim_matches('/').to_string();
+        let query = req.uri().query().unwrap_or_default().to_string();
+        let segments: Vec<&str> = path.split('/').collect();
+        debug!("{method} /{path}");
+
+        let result = match (method, segments.as_slice()) {
+            (Method::POST, ["lnurlpay", pubkey]) => match read_json(req).await {
+                Ok(body) => self.register(pubkey, &body),
+                Err(e) => Err(e),
+            },
+            (Method::POST, ["lnurlpay", pubkey, "unregister"]) => match read_json(req).await {
+                Ok(body) => self.unregister(pubkey, &body),
+                Err(e) => Err(e),
+            },
+            (Method::GET, [".well-known", "lnurlp", username]) => self.pay_request(username),
+            (Method::GET, ["lnurlp", username, "invoice"]) => self.invoice(username, &query).await,
+            _ => Err(ApiError::NotFound),
+        };
+        Ok(result.unwrap_or_else(ApiError::into_response))
+    }
+
+    fn register(
+        &self,
+        pubkey: &str,
+        req: &RegisterLnurlPayRequest,
+    ) -> Result<Response<Full<Bytes>>, ApiError> {
+        validate_username(&req.username).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        verify_signature(
+            pubkey,
+            &register_message(req.time, &req.username, &req.webhook_url),
+            &req.signature,
+            req.time,
+        )?;
+
+        let mut registrations = self
+            .registrations
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if registrations
+            .get(&req.username)
+            .is_some_and(|registration| registration.pubkey != pubkey)
+        {
+            return Err(ApiError::Conflict);
+        }
+        // A wallet has a single username, so registering another one releases the previous
+        registrations.retain(|_, registration| registration.pubkey != pubkey);
+        registrations.insert(
+            req.username.clone(),
+            Registration {
+                pubkey: pubkey.to_string(),
+                webhook_url: req.webhook_url.clone(),
+            },
+        );
+        info!("Registered {} for {pubkey}", req.username);
+
+        let url = format!("{}/.well-known/lnurlp/{}", self.base_url, req.username);
+        let lnurl = bech32::encode::<Bech32>(Hrp::parse_unchecked("lnurl"), url.as_bytes())
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        Ok(json_response(
+            StatusCode::OK,
+            &RegisterLnurlPayResponse {
+                lightning_address: format!("{}@{}", req.username, self.domain),
+                lnurl,
+            },
+        ))
+    }
+
+    fn unregister(
+        &self,
+        pubkey: &str,
+        req: &UnregisterLnurlPayRequest,
+    ) -> Result<Response<Full<Bytes>>, ApiError> {
+        verify_signature(
+            pubkey,
+            &unregister_message(req.time, &req.webhook_url),
+            &req.signature,
+            req.time,
+        )?;
+
+        let mut registrations = self
+            .registrations
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let count = registrations.len();
+        registrations.retain(|_, registration| {
+            registration.pubkey != pubkey || registration.webhook_url != req.webhook_url
+        });
+        if registrations.len() == count {
+            return Err(ApiError::NotFound);
+        }
+        info!("Unregistered {pubkey}");
+        Ok(json_response(StatusCode::OK, &LnurlCallbackStatus::Ok))
+    }
+
+    /// The LUD-06 pay request of the Lightning address
+    fn pay_request(&self, username: &str) -> Result<Response<Full<Bytes>>, ApiError> {
+        self.webhook_url(username)?;
+        Ok(json_response(
+            StatusCode::OK,
+            &json!({
+                "callback": format!("{}/lnurlp/{username}/invoice", self.base_url),
+                "minSendable": MIN_SENDABLE_MSAT,
+                "maxSendable": MAX_SENDABLE_MSAT,
+                "metadata": lnurl_pay_metadata(&format!("{username}@{}", self.domain)),
+                "tag": "payRequest",
+            }),
+        ))
+    }
+
+    /// Requests an invoice from the webhook of the wallet and relays it to the payer.
+    async fn invoice(
+        &self,
+        username: &str,
+        query: &str,
+    ) -> Result<Response<Full<Bytes>>, ApiError> {
+        let webhook_url = self.webhook_url(username)?;
+        let amount_msat = query
+            .split('&')
+            .find_map(|param| param.strip_prefix("amount="))
+            .and_then(|amount| amount.parse::<u64>().ok())
+            .filter(|amount| (MIN_SENDABLE_MSAT..=MAX_SENDABLE_MSAT).contains(amount))
+            .ok_or_else(|| ApiError::BadRequest("invalid amount".to_string()))?;
+        let metadata = lnurl_pay_metadata(&format!("{username}@{}", self.domain));
+
+        let headers = HashMap::from([("Content-Type".to_string(), "application/json".to_string())]);
+        let body = serde_json::to_string(&LnurlPayInvoiceRequest {
+            amount_msat,
+            metadata: metadata.clone(),
+        })
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        let (response, status) = self
+            .rest_client
+            .post(&webhook_url, Some(headers), Some(body))
+            .await
+            .map_err(|e| ApiError::Webhook(format!("webhook request failed: {e}")))?;
+        if !(200..300).contains(&status) {
+            return Err(ApiError::Webhook(format!(
+                "webhook failed with status {status}"
+            )));
+        }
+        let response: LnurlPayInvoiceResponse = parse_json(&response)
+            .map_err(|e| ApiError::Webhook(format!("invalid webhook response: {e}")))?;
+
+        // Payers reject invoices not matching the request, so catch misbehaving wallets early
+        let invoice = Bolt11Invoice::from_str(&response.pr)
+            .map_err(|e| ApiError::Webhook(format!("invalid invoice: {e}")))?;
+        if invoice.amount_milli_satoshis() != Some(amount_msat) {
+            return Err(ApiError::Webhook("invoice amount mismatch".to_string()));
+        }
+        let metadata_hash = sha256::Hash::hash(metadata.as_bytes());
+        if !matches!(invoice.description(), Bolt11InvoiceDescriptionRef::Hash(hash) if hash.0 == metadata_hash)
+        {
+            return Err(ApiError::Webhook(
+                "invoice does not commit to the metadata".to_string(),
+            ));
+        }
+
+        Ok(json_response(
+            StatusCode::OK,
+            &json!({ "pr": response.pr, "routes": [] }),
+        ))
+    }
+
+    fn webhook_url(&self, username: &str) -> Result<String, ApiError> {
+        self.registrations
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(username)
+            .map(|registration| registration.webhook_url.clone())
+            .ok_or(ApiError::NotFound)
+    }
+}
+
+/// Checks the request is recent and signed by the node key of the wallet.
+fn verify_signature(
+    pubkey: &str,
+    message: &str,
+    signature: &str,
+    time: u64,
+) -> Result<(), ApiError> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?
+        .as_secs();
+    if now.abs_diff(time) > MAX_TIME_DRIFT_SECS {
+        return Err(ApiError::BadRequest("request expired".to_string()));
+    }
+
+    let pubkey = PublicKey::from_str(pubkey)
+        .map_err(|_| ApiError::BadRequest("invalid pubkey".to_string()))?;
+    if !message_signing::verify(message.as_bytes(), signature, &pubkey) {
+        return Err(ApiError::Unauthorized);
+    }
+    Ok(())
+}
+
+async fn read_json<T: DeserializeOwned>(req: Request<Incoming>) -> Result<T, ApiError> {
+    let body = req
+        .into_body()
+        .collect()
+        .await
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?
+        .to_bytes();
+    serde_json::from_slice(&body).map_err(|e| ApiError::BadRequest(e.to_string()))
+}
+
+fn json_response<T: Serialize>(status: StatusCode, body: &T) -> Response<Full<Bytes>> {
+    let body = serde_json::to_vec(body).unwrap_or_default();
+    let mut response = Response::new(Full::new(Bytes::from(body)));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}